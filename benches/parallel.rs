@@ -0,0 +1,93 @@
+//! `absorb_parallel`/ `squeeze_parallel` are offered only by serial Xoofff, so nothing is
+//! benchmarked when `simd` feature is enabled, which makes `xoofff::Xoofff` the SIMD one.
+
+#[cfg(not(feature = "simd"))]
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+#[cfg(not(feature = "simd"))]
+use rand::{thread_rng, RngCore};
+#[cfg(not(feature = "simd"))]
+use xoofff::Xoofff;
+
+#[cfg(not(feature = "simd"))]
+fn absorb<const MLEN: usize>(c: &mut Criterion) {
+    let mut rng = thread_rng();
+
+    let mut key = [0u8; 32];
+    let mut msg = vec![0u8; MLEN];
+    let mut dig = [0u8; 32];
+
+    rng.fill_bytes(&mut key);
+    rng.fill_bytes(&mut msg);
+
+    let mut group = c.benchmark_group("xoofff");
+    group.throughput(Throughput::Bytes(MLEN as u64));
+    group.sample_size(10);
+
+    group.bench_function(format!("absorb (serial) | in = {}", MLEN), |bench| {
+        bench.iter(|| {
+            let mut deck = Xoofff::new(black_box(&key));
+            deck.absorb(black_box(&msg));
+            deck.finalize(0, 0, 0);
+            deck.squeeze(black_box(&mut dig));
+        });
+    });
+
+    group.bench_function(format!("absorb (parallel) | in = {}", MLEN), |bench| {
+        bench.iter(|| {
+            let mut deck = Xoofff::new(black_box(&key));
+            deck.absorb_parallel(black_box(&msg));
+            deck.finalize(0, 0, 0);
+            deck.squeeze(black_box(&mut dig));
+        });
+    });
+}
+
+#[cfg(not(feature = "simd"))]
+fn squeeze<const DLEN: usize>(c: &mut Criterion) {
+    let mut rng = thread_rng();
+
+    let mut key = [0u8; 32];
+    let mut msg = [0u8; 32];
+    let mut dig = vec![0u8; DLEN];
+
+    rng.fill_bytes(&mut key);
+    rng.fill_bytes(&mut msg);
+
+    let mut group = c.benchmark_group("xoofff");
+    group.throughput(Throughput::Bytes(DLEN as u64));
+    group.sample_size(10);
+
+    group.bench_function(format!("squeeze (serial) | out = {}", DLEN), |bench| {
+        bench.iter(|| {
+            let mut deck = Xoofff::new(black_box(&key));
+            deck.absorb(black_box(&msg));
+            deck.finalize(0, 0, 0);
+            deck.squeeze(black_box(&mut dig));
+        });
+    });
+
+    group.bench_function(format!("squeeze (parallel) | out = {}", DLEN), |bench| {
+        bench.iter(|| {
+            let mut deck = Xoofff::new(black_box(&key));
+            deck.absorb(black_box(&msg));
+            deck.finalize(0, 0, 0);
+            deck.squeeze_parallel(black_box(&mut dig));
+        });
+    });
+}
+
+#[cfg(not(feature = "simd"))]
+criterion_group!(
+    parallel,
+    absorb::<{ 1 << 20 }>,
+    absorb::<{ 1 << 24 }>,
+    absorb::<{ 1 << 27 }>,
+    squeeze::<{ 1 << 20 }>,
+    squeeze::<{ 1 << 24 }>,
+    squeeze::<{ 1 << 27 }>
+);
+#[cfg(not(feature = "simd"))]
+criterion_main!(parallel);
+
+#[cfg(feature = "simd")]
+fn main() {}
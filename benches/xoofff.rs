@@ -66,6 +66,159 @@ fn xoofff<
     );
 }
 
+fn absorb<const MLEN: usize>(c: &mut Criterion) {
+    let mut rng = thread_rng();
+
+    let mut group = c.benchmark_group("xoofff");
+    group.throughput(Throughput::Bytes(MLEN as u64));
+
+    let mut key = [0u8; 32];
+    let mut msg = vec![0u8; MLEN];
+
+    rng.fill_bytes(&mut key);
+    rng.fill_bytes(&mut msg);
+
+    // whole message at once, so that all full blocks are read straight from input slice
+    group.bench_function(format!("absorb | in = {} (full blocks)", MLEN), |bench| {
+        bench.iter(|| {
+            let mut deck = Xoofff::new(black_box(&key));
+            deck.absorb(black_box(&msg));
+            black_box(deck);
+        });
+    });
+
+    // 47 -bytes chunks, so that each message block has to be staged in internal buffer
+    group.bench_function(format!("absorb | in = {} (47B chunks)", MLEN), |bench| {
+        bench.iter(|| {
+            let mut deck = Xoofff::new(black_box(&key));
+            for chunk in msg.chunks(47) {
+                deck.absorb(black_box(chunk));
+            }
+            black_box(deck);
+        });
+    });
+}
+
+/// Compares absorbing with one Xoodoo permutation at a time against two of them interleaved, which
+/// decides `farfalle::ILP_WIDTH` of target, when no SIMD implementation is enabled.
+fn ilp<const MLEN: usize>(c: &mut Criterion) {
+    use xoofff::farfalle::{Farfalle, RollXc, RollXe, Xoodoo};
+
+    let mut rng = thread_rng();
+
+    let mut group = c.benchmark_group("xoofff");
+    group.throughput(Throughput::Bytes(MLEN as u64));
+
+    let mut key = [0u8; 32];
+    let mut msg = vec![0u8; MLEN];
+
+    rng.fill_bytes(&mut key);
+    rng.fill_bytes(&mut msg);
+
+    group.bench_function(format!("absorb | in = {} (ilp = 1)", MLEN), |bench| {
+        bench.iter(|| {
+            let mut deck = Farfalle::<Xoodoo<6, 1>, RollXc, RollXe>::new(black_box(&key));
+            deck.absorb(black_box(&msg));
+            black_box(deck);
+        });
+    });
+
+    group.bench_function(format!("absorb | in = {} (ilp = 2)", MLEN), |bench| {
+        bench.iter(|| {
+            let mut deck = Farfalle::<Xoodoo<6, 2>, RollXc, RollXe>::new(black_box(&key));
+            deck.absorb(black_box(&msg));
+            black_box(deck);
+        });
+    });
+}
+
+fn mac_many<const CNT: usize, const MLEN: usize>(c: &mut Criterion) {
+    let mut rng = thread_rng();
+
+    let mut group = c.benchmark_group("xoofff");
+    group.throughput(Throughput::Bytes((CNT * MLEN) as u64));
+
+    let mut keys = vec![[0u8; 32]; CNT];
+    let mut msgs = vec![vec![0u8; MLEN]; CNT];
+    let mut tags = vec![[0u8; 16]; CNT];
+
+    keys.iter_mut().for_each(|key| rng.fill_bytes(key));
+    msgs.iter_mut().for_each(|msg| rng.fill_bytes(msg));
+
+    group.bench_function(
+        format!("{} MACs | in = {} | out = 16 (one by one)", CNT, MLEN),
+        |bench| {
+            bench.iter(|| {
+                for ((key, msg), tag) in keys.iter().zip(msgs.iter()).zip(tags.iter_mut()) {
+                    let mut deck = Xoofff::new(black_box(key));
+                    deck.absorb(black_box(msg));
+                    deck.finalize(0, 0, 0);
+                    deck.squeeze(black_box(tag));
+                }
+            });
+        },
+    );
+
+    group.bench_function(
+        format!("{} MACs | in = {} | out = 16 (mac_many)", CNT, MLEN),
+        |bench| {
+            let keys = keys.iter().map(|key| key.as_slice()).collect::<Vec<_>>();
+            let msgs = msgs.iter().map(|msg| msg.as_slice()).collect::<Vec<_>>();
+
+            bench.iter(|| {
+                let mut outs = tags
+                    .iter_mut()
+                    .map(|tag| tag.as_mut_slice())
+                    .collect::<Vec<_>>();
+                Xoofff::mac_many(black_box(&keys), black_box(&msgs), black_box(&mut outs));
+            });
+        },
+    );
+}
+
+#[cfg(all(
+    feature = "simd",
+    feature = "std",
+    any(target_arch = "x86", target_arch = "x86_64")
+))]
+fn backends<const MLEN: usize>(c: &mut Criterion) {
+    use xoofff::Backend;
+
+    let mut rng = thread_rng();
+
+    let mut group = c.benchmark_group("xoofff");
+    group.throughput(Throughput::Bytes((MLEN + 32) as u64));
+
+    let mut key = [0u8; 32];
+    let mut msg = vec![0u8; MLEN];
+    let mut dig = [0u8; 32];
+
+    rng.fill_bytes(&mut key);
+    rng.fill_bytes(&mut msg);
+
+    // each backend, supported by executing CPU, explicitly selected
+    for backend in Backend::available() {
+        group.bench_function(
+            format!("key = 32 | in = {} | out = 32 ({:?})", MLEN, backend),
+            |bench| {
+                bench.iter(|| {
+                    let mut deck = Xoofff::with_backend(black_box(&key), backend).unwrap();
+                    deck.absorb(black_box(&msg));
+                    deck.finalize(0, 0, 0);
+                    deck.squeeze(black_box(&mut dig));
+                });
+            },
+        );
+    }
+}
+
+#[cfg(all(
+    feature = "simd",
+    feature = "std",
+    any(target_arch = "x86", target_arch = "x86_64")
+))]
+criterion_group!(backend, backends::<1024>, backends::<65536>);
+
 criterion_group!(
     deck_function,
     xoofff::<32, 32, 32, 16>,
@@ -76,5 +229,23 @@ criterion_group!(
     xoofff::<32, 1024, 32, 16>,
     xoofff::<32, 2048, 32, 16>,
     xoofff::<32, 4096, 32, 16>,
+    absorb::<256>,
+    absorb::<4096>,
+    absorb::<65536>,
+    absorb::<1048576>,
+    ilp::<65536>,
+    mac_many::<64, 64>,
+    mac_many::<64, 256>,
 );
+#[cfg(not(all(
+    feature = "simd",
+    feature = "std",
+    any(target_arch = "x86", target_arch = "x86_64")
+)))]
 criterion_main!(deck_function);
+#[cfg(all(
+    feature = "simd",
+    feature = "std",
+    any(target_arch = "x86", target_arch = "x86_64")
+))]
+criterion_main!(deck_function, backend);
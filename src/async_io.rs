@@ -0,0 +1,156 @@
+//! Asynchronous absorbing/ squeezing adapters over Xoofff deck function, for `tokio` and
+//! `futures-io` ecosystems.
+//!
+//! Both `Absorber` and `Squeezer` ( see `Xoofff::absorber` and `Xoofff::squeezer` ) implement
+//! respective `AsyncWrite`/ `AsyncRead` traits. Deck function consumes/ produces bytes without
+//! ever blocking, so each poll completes immediately, accepting/ filling whole buffer it's given,
+//! which leaves backpressure entirely to the other side of the pipe.
+
+#[cfg(feature = "tokio")]
+pub mod tokio {
+    use crate::io::{Absorber, Squeezer, IO_BUF_SIZE};
+    use crate::Xoofff;
+    use ::tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+    use core::future::poll_fn;
+    use core::pin::Pin;
+    use core::task::{Context, Poll};
+    use std::io::{self, Read, Write};
+
+    impl AsyncWrite for Absorber<'_> {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            Poll::Ready(self.get_mut().write(buf))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    impl AsyncRead for Squeezer<'_> {
+        fn poll_read(
+            self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<io::Result<()>> {
+            let n = match self.get_mut().read(buf.initialize_unfilled()) {
+                Ok(n) => n,
+                Err(e) => return Poll::Ready(Err(e)),
+            };
+
+            buf.advance(n);
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    /// Absorbs all bytes, that can be read from `reader` until it reaches EOF, into Xoofff deck
+    /// function, keyed with `key` ( of byte length < 48 ), finalizes it with empty domain seperator
+    /// and zero offset, filling `tag` with squeezed bytes. Returns number of absorbed bytes.
+    pub async fn mac_stream<R: AsyncRead + Unpin>(
+        key: &[u8],
+        mut reader: R,
+        tag: &mut [u8],
+    ) -> io::Result<u64> {
+        let mut deck = Xoofff::new(key);
+        let mut buf = vec![0u8; IO_BUF_SIZE]; // on heap, so that future isn't IO_BUF_SIZE -bytes larger
+        let mut total = 0u64;
+
+        loop {
+            let mut rbuf = ReadBuf::new(&mut buf);
+            match poll_fn(|cx| Pin::new(&mut reader).poll_read(cx, &mut rbuf)).await {
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                res => res?,
+            };
+
+            let n = rbuf.filled().len();
+            if n == 0 {
+                break;
+            }
+
+            deck.absorb(&buf[..n]);
+            total += n as u64;
+        }
+
+        deck.finalize(0, 0, 0);
+        deck.squeeze(tag);
+
+        Ok(total)
+    }
+}
+
+#[cfg(feature = "futures-io")]
+pub mod futures {
+    use crate::io::{Absorber, Squeezer, IO_BUF_SIZE};
+    use crate::Xoofff;
+    use ::futures_io::{AsyncRead, AsyncWrite};
+    use core::future::poll_fn;
+    use core::pin::Pin;
+    use core::task::{Context, Poll};
+    use std::io::{self, Read, Write};
+
+    impl AsyncWrite for Absorber<'_> {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            Poll::Ready(self.get_mut().write(buf))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    impl AsyncRead for Squeezer<'_> {
+        fn poll_read(
+            self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            buf: &mut [u8],
+        ) -> Poll<io::Result<usize>> {
+            Poll::Ready(self.get_mut().read(buf))
+        }
+    }
+
+    /// Absorbs all bytes, that can be read from `reader` until it reaches EOF, into Xoofff deck
+    /// function, keyed with `key` ( of byte length < 48 ), finalizes it with empty domain seperator
+    /// and zero offset, filling `tag` with squeezed bytes. Returns number of absorbed bytes.
+    pub async fn mac_stream<R: AsyncRead + Unpin>(
+        key: &[u8],
+        mut reader: R,
+        tag: &mut [u8],
+    ) -> io::Result<u64> {
+        let mut deck = Xoofff::new(key);
+        let mut buf = vec![0u8; IO_BUF_SIZE]; // on heap, so that future isn't IO_BUF_SIZE -bytes larger
+        let mut total = 0u64;
+
+        loop {
+            let n = match poll_fn(|cx| Pin::new(&mut reader).poll_read(cx, &mut buf)).await {
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                res => res?,
+            };
+            if n == 0 {
+                break;
+            }
+
+            deck.absorb(&buf[..n]);
+            total += n as u64;
+        }
+
+        deck.finalize(0, 0, 0);
+        deck.squeeze(tag);
+
+        Ok(total)
+    }
+}
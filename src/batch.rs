@@ -0,0 +1,221 @@
+#[cfg(any(feature = "simd", feature = "intrinsics"))]
+use crate::rolling;
+#[cfg(any(feature = "simd", feature = "intrinsics"))]
+use crate::xoofff::{bytes_to_le_words, pad10x, wipe, words_to_le_bytes};
+use crate::Xoofff;
+#[cfg(any(feature = "simd", feature = "intrinsics"))]
+use core::cmp;
+
+/// Xoodoo\[n_r\] being a 384 -bit permutation, messages are consumed in 48 -bytes chunks
+#[cfg(any(feature = "simd", feature = "intrinsics"))]
+const BLOCK_SIZE: usize = 48;
+
+/// \# -of lanes ( each of 32 -bit width ) in Xoodoo permutation state
+#[cfg(any(feature = "simd", feature = "intrinsics"))]
+const LANE_CNT: usize = BLOCK_SIZE / core::mem::size_of::<u32>();
+
+/// \# -of independent deck function instances processed together, which matches width of
+/// parallel permutation of selected SIMD backend. With `simd` and `std` features, on x86/ x86_64,
+/// it's instead picked at runtime, by `simd::mac_many`, from the best suited backend.
+#[cfg(all(not(feature = "simd"), feature = "intrinsics"))]
+const LANES: usize = crate::intrinsics::PAR_WIDTH;
+#[cfg(all(feature = "simd", not(feature = "std"), target_feature = "avx512f"))]
+const LANES: usize = 16;
+#[cfg(all(
+    feature = "simd",
+    not(feature = "std"),
+    target_feature = "avx2",
+    not(target_feature = "avx512f")
+))]
+const LANES: usize = 8;
+#[cfg(all(
+    feature = "simd",
+    not(all(feature = "std", any(target_arch = "x86", target_arch = "x86_64"))),
+    not(target_feature = "avx2"),
+    not(target_feature = "avx512f")
+))]
+const LANES: usize = 4;
+
+/// Applies Xoodoo\[6\] permutation on each of N independent states, 8 at a time.
+#[cfg(all(not(feature = "simd"), feature = "intrinsics"))]
+#[inline(always)]
+fn permute_lanes<const N: usize>(states: &mut [[u32; LANE_CNT]; N]) {
+    use crate::intrinsics::{permute_x8, PAR_WIDTH};
+
+    let mut grps = states.chunks_exact_mut(PAR_WIDTH);
+    for grp in grps.by_ref() {
+        permute_x8::<6>(grp.try_into().unwrap());
+    }
+    for state in grps.into_remainder() {
+        crate::xoodoo::permute_fast::<6>(state);
+    }
+}
+
+#[cfg(feature = "simd")]
+use crate::simd::permute_lanes;
+
+impl Xoofff {
+    /// Computes MACs over many independent ( key, message ) pairs, at once, s.t. `i` -th key ( of
+    /// byte length < 48 ) is used for absorbing `i` -th message and `i` -th output slice is filled
+    /// with squeezed bytes, same as `Xoofff::new(key)`, `absorb(msg)`, `finalize(0, 0, 0)` and
+    /// `squeeze(out)` would do, for each pair. So only messages of full bytes, without any domain
+    /// seperator bits, are supported.
+    ///
+    /// Note, every instance is finalized with `finalize(0, 0, 0)` i.e. empty domain seperator and
+    /// zero offset. Anything else needs `Xoofff` to be driven one instance at a time.
+    ///
+    /// With `simd` or `intrinsics` feature, instances are processed in groups, s.t. each one
+    /// occupies a lane of parallel permutation of SIMD backend, so that even short messages ( e.g.
+    /// a few blocks each ) benefit from SIMD. With `simd` feature, backend is picked at runtime,
+    /// same as `Xoofff::new` does, wherever runtime CPU feature detection is possible. Messages
+    /// ( and outputs ) may have different byte lengths, lanes which are done early simply idle, so
+    /// it's best to group messages of similar length together. Without any of those features, it's
+    /// no faster than computing MACs one by one, which is exactly what it does then.
+    pub fn mac_many(keys: &[&[u8]], msgs: &[&[u8]], outs: &mut [&mut [u8]]) {
+        assert!(
+            keys.len() == msgs.len() && msgs.len() == outs.len(),
+            "Must have same number of keys, messages and outputs"
+        );
+
+        #[cfg(all(
+            feature = "simd",
+            feature = "std",
+            any(target_arch = "x86", target_arch = "x86_64")
+        ))]
+        {
+            use crate::simd::Backend;
+
+            let backend = Backend::available().last().unwrap_or(Backend::Serial);
+            crate::simd::mac_many(keys, msgs, outs, backend);
+        }
+
+        #[cfg(all(
+            any(feature = "simd", feature = "intrinsics"),
+            not(all(
+                feature = "simd",
+                feature = "std",
+                any(target_arch = "x86", target_arch = "x86_64")
+            ))
+        ))]
+        mac_groups::<LANES>(keys, msgs, outs);
+
+        #[cfg(all(not(feature = "simd"), not(feature = "intrinsics")))]
+        for ((key, msg), out) in keys.iter().zip(msgs.iter()).zip(outs.iter_mut()) {
+            let mut deck = Xoofff::new(key);
+            deck.absorb(msg);
+            deck.finalize(0, 0, 0);
+            deck.squeeze(out);
+        }
+    }
+}
+
+/// Computes MACs over many independent ( key, message ) pairs, in groups of N, s.t. each
+/// instance of a group occupies its own lane.
+#[cfg(any(feature = "simd", feature = "intrinsics"))]
+#[inline(always)]
+pub(crate) fn mac_groups<const N: usize>(keys: &[&[u8]], msgs: &[&[u8]], outs: &mut [&mut [u8]]) {
+    for ((keys, msgs), outs) in keys.chunks(N).zip(msgs.chunks(N)).zip(outs.chunks_mut(N)) {
+        mac_lanes::<N>(keys, msgs, outs);
+    }
+}
+
+/// Computes MACs over <= N independent ( key, message ) pairs, each in its own lane.
+#[cfg(any(feature = "simd", feature = "intrinsics"))]
+#[inline(always)]
+fn mac_lanes<const N: usize>(keys: &[&[u8]], msgs: &[&[u8]], outs: &mut [&mut [u8]]) {
+    let cnt = keys.len();
+    debug_assert!(cnt <= N);
+
+    // masked key derivation phase
+    let mut imask = [[0u32; LANE_CNT]; N];
+    for (mask, key) in imask.iter_mut().zip(keys.iter()) {
+        debug_assert!(
+            key.len() < BLOCK_SIZE,
+            "Key byte length must be < {}",
+            BLOCK_SIZE
+        );
+        *mask = bytes_to_le_words(&pad10x(key));
+    }
+    permute_lanes(&mut imask);
+
+    // compression phase, last block of each message is padded with empty domain seperator
+    let blk_cnt = msgs.iter().map(|msg| msg.len() / BLOCK_SIZE + 1).max();
+
+    let mut acc = [[0u32; LANE_CNT]; N];
+    let mut words = [[0u32; LANE_CNT]; N];
+
+    for t in 0..blk_cnt.unwrap_or(0) {
+        let off = t * BLOCK_SIZE;
+
+        for i in 0..cnt {
+            let msg = msgs[i];
+            if off > msg.len() {
+                continue;
+            }
+
+            words[i] = if off + BLOCK_SIZE <= msg.len() {
+                bytes_to_le_words(msg[off..off + BLOCK_SIZE].try_into().unwrap())
+            } else {
+                bytes_to_le_words(&pad10x(&msg[off..]))
+            };
+            words[i]
+                .iter_mut()
+                .zip(imask[i].iter())
+                .for_each(|(w, m)| *w ^= *m);
+
+            rolling::roll_xc(&mut imask[i]);
+        }
+
+        permute_lanes(&mut words);
+
+        for i in 0..cnt {
+            if off <= msgs[i].len() {
+                acc[i]
+                    .iter_mut()
+                    .zip(words[i].iter())
+                    .for_each(|(a, w)| *a ^= *w);
+            }
+        }
+    }
+
+    for mask in imask.iter_mut().take(cnt) {
+        rolling::roll_xc(mask);
+    }
+
+    // expansion phase
+    let mut omask = acc;
+    permute_lanes(&mut omask);
+
+    let blk_cnt = outs.iter().map(|out| out.len().div_ceil(BLOCK_SIZE)).max();
+    let mut blk = [0u8; BLOCK_SIZE];
+
+    for t in 0..blk_cnt.unwrap_or(0) {
+        let off = t * BLOCK_SIZE;
+
+        words = omask;
+        permute_lanes(&mut words);
+
+        for i in 0..cnt {
+            let out = &mut outs[i];
+            if off >= out.len() {
+                continue;
+            }
+
+            words[i]
+                .iter_mut()
+                .zip(imask[i].iter())
+                .for_each(|(w, m)| *w ^= *m);
+            words_to_le_bytes(&words[i], &mut blk);
+
+            let len = cmp::min(BLOCK_SIZE, out.len() - off);
+            out[off..off + len].copy_from_slice(&blk[..len]);
+
+            rolling::roll_xe(&mut omask[i]);
+        }
+    }
+
+    for state in [&mut imask, &mut acc, &mut omask, &mut words] {
+        state.iter_mut().for_each(|words| wipe(words));
+    }
+    wipe(&mut blk);
+}
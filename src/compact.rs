@@ -0,0 +1,264 @@
+//! Xoofff deck function, with a small memory footprint, for RAM constrained devices.
+
+use crate::rolling;
+use crate::xoodoo;
+use crate::xoofff::{bytes_to_le_words, compress_blocks, words_to_le_bytes, xor_bytes};
+use core::cmp;
+#[cfg(feature = "zeroize")]
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// Xoodoo\[n_r\] being a 384 -bit permutation, messages are consumed in 48 -bytes chunks
+const BLOCK_SIZE: usize = 48;
+
+/// \# -of rounds of Xoodoo\[n_r\] permutation, used in Xoofff
+const ROUNDS: usize = 6;
+
+/// \# -of lanes ( each of 32 -bit width ) in Xoodoo permutation state
+const LANE_CNT: usize = BLOCK_SIZE / core::mem::size_of::<u32>();
+
+/// Xoofff deck function, producing exactly same output as `crate::Xoofff`, while keeping a state of
+/// less than 150 -bytes, so that it fits on RAM constrained devices such as sensor nodes.
+///
+/// It only keeps input mask, accumulator and a single 48 -bytes buffer, which holds partially
+/// filled message block while absorbing and output mask while squeezing. Output blocks are not
+/// buffered, but worked out on demand, from output mask, so squeezing a partial block costs a
+/// permutation each time, it's best to squeeze in multiples of 48 -bytes, whenever possible.
+/// Random-access reads of output ( i.e. `squeeze_at` ) are not supported, because that needs the
+/// output mask of very first output block to be kept around.
+///
+/// With `zeroize` feature, deck function state is wiped on drop and the type isn't `Copy`.
+#[cfg_attr(not(feature = "zeroize"), derive(Clone, Copy))]
+#[cfg_attr(feature = "zeroize", derive(Clone))]
+pub struct Xoofff {
+    imask: [u32; LANE_CNT], // input mask
+    acc: [u32; LANE_CNT],   // accumulator
+    buf: [u8; BLOCK_SIZE],  // message block, while absorbing, output mask, while squeezing
+    off: u8,                // offset into message/ output block
+    finalized: bool,        // is deck function state finalized ?
+}
+
+impl Xoofff {
+    /// Create a new instance of Xoofff, with a key of byte length < 48, which
+    /// can be used for incrementally absorbing messages and squeezing output bytes.
+    #[inline(always)]
+    pub fn new(key: &[u8]) -> Self {
+        debug_assert!(
+            key.len() < BLOCK_SIZE,
+            "Key byte length must be < {}",
+            BLOCK_SIZE
+        );
+
+        let mut padded_key = crate::xoofff::pad10x(key);
+        let mut imask = bytes_to_le_words(&padded_key);
+        crate::xoofff::wipe(&mut padded_key);
+        xoodoo::permute_fast::<ROUNDS>(&mut imask);
+
+        let deck = Self {
+            imask,
+            acc: [0u32; LANE_CNT],
+            buf: [0u8; BLOCK_SIZE],
+            off: 0,
+            finalized: false,
+        };
+
+        crate::xoofff::wipe(&mut imask);
+        deck
+    }
+
+    /// Absorbs message bytes into deck function state, see `crate::Xoofff::absorb`.
+    #[inline(always)]
+    pub fn absorb(&mut self, msg: &[u8]) {
+        if self.finalized {
+            return;
+        }
+
+        let mut msg = msg;
+        let off = self.off as usize;
+
+        // complete partially filled message block, staged in buffer, if any
+        if off > 0 {
+            let byte_cnt = cmp::min(BLOCK_SIZE - off, msg.len());
+
+            self.buf[off..(off + byte_cnt)].copy_from_slice(&msg[..byte_cnt]);
+            self.off += byte_cnt as u8;
+            msg = &msg[byte_cnt..];
+
+            if (self.off as usize) < BLOCK_SIZE {
+                return;
+            }
+
+            compress_blocks(&mut self.imask, &mut self.acc, &self.buf);
+            self.off = 0;
+        }
+
+        let (blks, rm) = msg.split_at(msg.len() - msg.len() % BLOCK_SIZE);
+        compress_blocks(&mut self.imask, &mut self.acc, blks);
+
+        self.buf[..rm.len()].copy_from_slice(rm);
+        self.off = rm.len() as u8;
+    }
+
+    /// Finalizes deck function state, skipping `offset` ( <= 48 ) -bytes of output, see
+    /// `crate::Xoofff::finalize`.
+    #[inline(always)]
+    pub fn finalize(&mut self, domain_seperator: u8, ds_bit_width: usize, offset: usize) {
+        debug_assert!(
+            offset <= BLOCK_SIZE,
+            "Byte offset, considered during squeezing, must be <= 48 -bytes"
+        );
+
+        self.finalize_with_offset(domain_seperator, ds_bit_width, offset as u64);
+    }
+
+    /// Finalizes deck function state, skipping arbitrary many bytes of output, see
+    /// `crate::Xoofff::finalize_with_offset`.
+    #[inline(always)]
+    pub fn finalize_with_offset(&mut self, domain_seperator: u8, ds_bit_width: usize, offset: u64) {
+        debug_assert!(
+            ds_bit_width <= 7,
+            "Domain seperator bit width is not allowed to be > 7"
+        );
+
+        if self.finalized {
+            return;
+        }
+
+        let mask = (1u8 << ds_bit_width) - 1u8;
+        let pad_byte = (1u8 << ds_bit_width) | (domain_seperator & mask);
+
+        let off = self.off as usize;
+        self.buf[off..].fill(0);
+        self.buf[off] = pad_byte;
+
+        compress_blocks(&mut self.imask, &mut self.acc, &self.buf);
+        rolling::roll_xc(&mut self.imask);
+
+        // from now on, buffer holds output mask
+        let mut omask = self.acc;
+        xoodoo::permute_fast::<ROUNDS>(&mut omask);
+        words_to_le_bytes(&omask, &mut self.buf);
+        crate::xoofff::wipe(&mut omask);
+
+        self.off = 0;
+        self.finalized = true;
+
+        self.skip(offset);
+    }
+
+    /// Squeezes bytes out of finalized deck function state, see `crate::Xoofff::squeeze`.
+    #[inline(always)]
+    pub fn squeeze(&mut self, out: &mut [u8]) {
+        self.squeeze_with(out, |dst, src| dst.copy_from_slice(src));
+    }
+
+    /// XORs squeezed bytes into `buf`, see `crate::Xoofff::squeeze_xor`.
+    #[inline(always)]
+    pub fn squeeze_xor(&mut self, buf: &mut [u8]) {
+        self.squeeze_with(buf, xor_bytes);
+    }
+
+    /// Works out output bytes, block by block, on demand, handing them over to `write`, along
+    /// with respective portion of `out`.
+    #[inline(always)]
+    fn squeeze_with(&mut self, out: &mut [u8], write: impl Fn(&mut [u8], &[u8])) {
+        if !self.finalized {
+            return;
+        }
+
+        let mut omask = bytes_to_le_words(&self.buf);
+        let mut words = [0u32; LANE_CNT];
+        let mut blk = [0u8; BLOCK_SIZE];
+        let mut out = out;
+
+        while !out.is_empty() {
+            let off = self.off as usize;
+            let read = cmp::min(BLOCK_SIZE - off, out.len());
+
+            words = omask;
+            xoodoo::permute_fast::<ROUNDS>(&mut words);
+            words
+                .iter_mut()
+                .zip(self.imask.iter())
+                .for_each(|(w, m)| *w ^= *m);
+            words_to_le_bytes(&words, &mut blk);
+
+            let (head, rest) = out.split_at_mut(read);
+            write(head, &blk[off..(off + read)]);
+            out = rest;
+
+            if off + read == BLOCK_SIZE {
+                rolling::roll_xe(&mut omask);
+                self.off = 0;
+            } else {
+                self.off += read as u8;
+            }
+        }
+
+        words_to_le_bytes(&omask, &mut self.buf);
+        crate::xoofff::wipe(&mut omask);
+        crate::xoofff::wipe(&mut words);
+        crate::xoofff::wipe(&mut blk);
+    }
+
+    /// Skips next `n` bytes of output, as if those were squeezed and thrown away, see
+    /// `crate::Xoofff::skip`. Costs O(n / 48) roll_Xe invocations. Panics if output position
+    /// overflows `u64`.
+    #[inline(always)]
+    pub fn skip(&mut self, n: u64) {
+        if !self.finalized {
+            return;
+        }
+
+        let pos = (self.off as u64).checked_add(n);
+        let pos = pos.expect("Output position must not overflow u64");
+
+        let mut omask = bytes_to_le_words(&self.buf);
+        for _ in 0..(pos / BLOCK_SIZE as u64) {
+            rolling::roll_xe(&mut omask);
+        }
+        words_to_le_bytes(&omask, &mut self.buf);
+        crate::xoofff::wipe(&mut omask);
+
+        self.off = (pos % BLOCK_SIZE as u64) as u8;
+    }
+
+    /// Restarts `absorb->finalize->squeeze` cycle, see `crate::Xoofff::restart`.
+    #[inline(always)]
+    pub fn restart(&mut self) {
+        if !self.finalized {
+            return;
+        }
+
+        crate::xoofff::wipe(&mut self.buf);
+        self.off = 0;
+        self.finalized = false;
+    }
+
+    /// Returns true if deck function state is finalized i.e. it's ready to be squeezed, but
+    /// can't absorb any more message bytes, until `absorb->finalize->squeeze` cycle is restarted.
+    #[inline(always)]
+    pub fn is_finalized(&self) -> bool {
+        self.finalized
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Zeroize for Xoofff {
+    fn zeroize(&mut self) {
+        self.imask.zeroize();
+        self.acc.zeroize();
+        self.buf.zeroize();
+        self.off.zeroize();
+        self.finalized.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for Xoofff {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl ZeroizeOnDrop for Xoofff {}
@@ -0,0 +1,791 @@
+//! Farfalle construction, generic over permutation and rolling functions, see https://ia.cr/2016/1188.
+
+#[cfg(feature = "intrinsics")]
+use crate::intrinsics::{self, PAR_WIDTH};
+use crate::rolling;
+use crate::xoodoo;
+use crate::xoofff::{bytes_to_le_words, wipe, words_to_le_bytes, xor_bytes, xor_le_words};
+use core::cmp;
+use core::marker::PhantomData;
+#[cfg(feature = "zeroize")]
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// Maximum \# -of states `Permutation::permute_many` is handed at once, by Farfalle.
+const MAX_PARALLELISM: usize = 8;
+
+/// Cryptographic permutation, which Farfalle can be instantiated with, for any of its phases
+/// ( i.e. mask derivation, compression, middle and expansion ).
+pub trait Permutation {
+    /// Permutation state e.g. an array of lanes.
+    type State: Copy;
+
+    /// Byte array of width of permutation, holding a message/ output block.
+    type Block: Copy + AsRef<[u8]> + AsMut<[u8]>;
+
+    /// Width of permutation, in bytes.
+    const WIDTH: usize;
+
+    /// All zero permutation state.
+    const ZERO: Self::State;
+
+    /// All zero block.
+    const ZERO_BLOCK: Self::Block;
+
+    /// \# -of states ( <= 8 ), `permute_many` permutes faster than one after another.
+    const PARALLELISM: usize = 1;
+
+    /// Applies permutation on given state.
+    fn permute(state: &mut Self::State);
+
+    /// Applies permutation on each of given independent states.
+    #[inline(always)]
+    fn permute_many(states: &mut [Self::State]) {
+        states.iter_mut().for_each(Self::permute);
+    }
+
+    /// Interprets `WIDTH` bytes as permutation state.
+    fn load(bytes: &[u8]) -> Self::State;
+
+    /// Writes permutation state as `WIDTH` bytes, inverse of `load`.
+    fn store(state: &Self::State, bytes: &mut [u8]);
+
+    /// XORs `src` permutation state into `dst`.
+    fn xor(dst: &mut Self::State, src: &Self::State);
+
+    /// XORs permutation state, written as `WIDTH` bytes, into `bytes`.
+    #[inline(always)]
+    fn xor_into(state: &Self::State, bytes: &mut [u8]) {
+        let mut blk = Self::ZERO_BLOCK;
+        Self::store(state, blk.as_mut());
+        xor_bytes(bytes, blk.as_ref());
+        wipe(blk.as_mut());
+    }
+}
+
+/// Rolling function, updating a permutation state, used by Farfalle for computing masks of
+/// consecutive message/ output blocks.
+pub trait RollingFunction<S> {
+    /// Rolls given state, once.
+    fn roll(state: &mut S);
+
+    /// Rolls given state `n` times, override it if that can be done faster than rolling one by one.
+    #[inline(always)]
+    fn jump(state: &mut S, n: u64) {
+        for _ in 0..n {
+            Self::roll(state);
+        }
+    }
+}
+
+/// Farfalle is a construction for building deck functions, from a cryptographic permutation
+/// P and two rolling functions, RC for input masks and RE for output masks. A secret key is
+/// turned into input mask, each message block is masked, permuted and accumulated, accumulator is
+/// permuted into output mask, which is rolled and permuted for computing each output block.
+///
+/// It supports incremental `absorb->finalize->squeeze` cycles, restartable any number of times,
+/// same as `crate::Xoofff`, which is simply Farfalle, instantiated with Xoodoo\[6\], roll_Xc and
+/// roll_Xe. So one can instantiate experimental Farfalle variants, by implementing `Permutation`
+/// and `RollingFunction`, for their own permutation and rolling functions.
+///
+/// By default, all phases use P. Optionally, compression, middle and expansion phases can be
+/// given their own permutations PC, PD and PE ( e.g. same permutation with different # -of
+/// rounds ), while P is then used only for mask derivation, see `crate::XoofffParams`.
+///
+/// See https://ia.cr/2016/1188 for definition of Farfalle.
+///
+/// With `zeroize` feature, deck function state is wiped on drop and the type isn't `Copy`.
+pub struct Farfalle<P: Permutation, RC, RE, PC = P, PD = P, PE = P> {
+    pub(crate) imask: P::State,  // input mask
+    pub(crate) ibase: P::State,  // input mask of very first input block, of current message
+    pub(crate) omask: P::State,  // output mask
+    pub(crate) obase: P::State,  // output mask of very first output block
+    pub(crate) acc: P::State,    // accumulator
+    pub(crate) iblk: P::Block,   // input message block ( buffer )
+    pub(crate) oblk: P::Block,   // output message block ( buffer )
+    pub(crate) ioff: usize,      // offset into input message block
+    pub(crate) ooff: usize,      // offset into output message block
+    pub(crate) oidx: u64,        // index of output block, currently held in output buffer
+    pub(crate) oskip: u64,       // # -of output bytes skipped during finalization
+    pub(crate) finalized: usize, // is deck function state finalized ?
+    pub(crate) _marker: PhantomData<(P, RC, RE, PC, PD, PE)>,
+}
+
+impl<P, RC, RE, PC, PD, PE> Farfalle<P, RC, RE, PC, PD, PE>
+where
+    P: Permutation,
+    PC: Permutation<State = P::State, Block = P::Block>,
+    PD: Permutation<State = P::State, Block = P::Block>,
+    PE: Permutation<State = P::State, Block = P::Block>,
+    RC: RollingFunction<P::State>,
+    RE: RollingFunction<P::State>,
+{
+    /// Create a new instance of deck function, with a key of byte length < width of permutation,
+    /// which can be used for incrementally absorbing messages and squeezing output bytes.
+    #[inline(always)]
+    pub fn new(key: &[u8]) -> Self {
+        debug_assert!(
+            key.len() < P::WIDTH,
+            "Key byte length must be < {}",
+            P::WIDTH
+        );
+
+        // masked key derivation phase
+        let mut padded_key = P::ZERO_BLOCK;
+        padded_key.as_mut()[..key.len()].copy_from_slice(key);
+        padded_key.as_mut()[key.len()] = 0x01;
+
+        let mut masked_key = P::load(padded_key.as_ref());
+        P::permute(&mut masked_key);
+        wipe(padded_key.as_mut());
+
+        let deck = Self {
+            imask: masked_key,
+            ibase: masked_key,
+            omask: P::ZERO,
+            obase: P::ZERO,
+            acc: P::ZERO,
+            iblk: P::ZERO_BLOCK,
+            oblk: P::ZERO_BLOCK,
+            ioff: 0,
+            ooff: 0,
+            oidx: 0,
+            oskip: 0,
+            finalized: usize::MIN,
+            _marker: PhantomData,
+        };
+
+        wipe_state(&mut masked_key, P::ZERO);
+        deck
+    }
+
+    /// Given a message M of byte length N (>=0), this routine can be used for absorbing
+    /// message bytes into the state of the deck function, following algorithm 1,
+    /// defined in Farfalle specification https://ia.cr/2016/1188.
+    ///
+    /// Note, this function can be called multiple times until deck function state is finalized.
+    /// Once finalized, bytes can be squeezed out of deck function state. Even after finalization
+    /// new absorption->finalization->squeezing phase can be started by calling restart function.
+    ///
+    /// Full message blocks are read straight from `msg`, only partial leading/ trailing message
+    /// bytes are staged in internal buffer.
+    #[inline(always)]
+    pub fn absorb(&mut self, msg: &[u8]) {
+        if self.finalized == usize::MAX {
+            return;
+        }
+
+        let mut msg = msg;
+
+        // complete partially filled message block, staged in buffer, if any
+        if self.ioff > 0 {
+            let byte_cnt = cmp::min(P::WIDTH - self.ioff, msg.len());
+
+            self.iblk.as_mut()[self.ioff..(self.ioff + byte_cnt)].copy_from_slice(&msg[..byte_cnt]);
+            self.ioff += byte_cnt;
+            msg = &msg[byte_cnt..];
+
+            if self.ioff < P::WIDTH {
+                return;
+            }
+
+            compress_blocks::<PC, RC>(&mut self.imask, &mut self.acc, self.iblk.as_ref());
+            self.ioff = 0;
+        }
+
+        // full message blocks are read straight from input slice, without any staging
+        let (blks, rm) = msg.split_at(msg.len() - msg.len() % P::WIDTH);
+        compress_blocks::<PC, RC>(&mut self.imask, &mut self.acc, blks);
+
+        self.iblk.as_mut()[..rm.len()].copy_from_slice(rm);
+        self.ioff = rm.len();
+    }
+
+    /// Absorbs a full message block, which is `index` -th ( zero based ) block of current
+    /// message, into deck function state. As accumulator is simply XOR of all masked and
+    /// permuted blocks, blocks can be absorbed in any order, input mask of the block is computed
+    /// by jumping ahead from input mask of very first block of current message.
+    ///
+    /// Same as `absorb_blocks_at`, with a single block. See there for caveats.
+    #[inline(always)]
+    pub fn absorb_block_at(&mut self, index: u64, blk: &P::Block) {
+        self.absorb_blocks_at(index, blk.as_ref());
+    }
+
+    /// Absorbs a run of consecutive full message blocks, s.t. first one is `index` -th ( zero
+    /// based ) block of current message, into deck function state, in any order w.r.t. other
+    /// blocks of same message. Input mask is jumped ahead only once per run, so prefer this
+    /// over `absorb_block_at` when a chunk spans multiple consecutive blocks.
+    ///
+    /// - Byte length of `msg` must be a multiple of width of permutation.
+    /// - Each block of a message must be absorbed exactly once, using either this routine or
+    ///   `absorb_block_at`, while last ( possibly empty ) partial block must be supplied using
+    ///   `absorb_final_block_at`, before finalizing the state.
+    /// - Don't mix with `absorb` for same message, as that one keeps track of block index itself.
+    /// - Attempting to absorb into already finalized state, does nothing.
+    pub fn absorb_blocks_at(&mut self, index: u64, msg: &[u8]) {
+        debug_assert!(
+            msg.len().is_multiple_of(P::WIDTH),
+            "Byte length of message blocks must be a multiple of {}",
+            P::WIDTH
+        );
+
+        if self.finalized == usize::MAX {
+            return;
+        }
+
+        let mut mask = self.ibase;
+        RC::jump(&mut mask, index);
+
+        compress_blocks::<PC, RC>(&mut mask, &mut self.acc, msg);
+
+        wipe_state(&mut mask, P::ZERO);
+    }
+
+    /// Supplies last partial ( i.e. of byte length < width of permutation, possibly empty ) block
+    /// of current message, s.t. `index` is total number of full blocks in message, so that state
+    /// can be finalized, once all full blocks are absorbed using `absorb_block_at`/
+    /// `absorb_blocks_at`. This routine itself doesn't permute anything, it only buffers the block
+    /// and sets up input mask, so it can be called before, after or in between absorbing full blocks.
+    ///
+    /// Attempting to supply final block to already finalized state, does nothing.
+    pub fn absorb_final_block_at(&mut self, index: u64, msg: &[u8]) {
+        debug_assert!(
+            msg.len() < P::WIDTH,
+            "Byte length of final message block must be < {}",
+            P::WIDTH
+        );
+
+        if self.finalized == usize::MAX {
+            return;
+        }
+
+        self.imask = self.ibase;
+        RC::jump(&mut self.imask, index);
+
+        self.iblk.as_mut()[..msg.len()].copy_from_slice(msg);
+        self.ioff = msg.len();
+    }
+
+    /// Given that arbitrary many message bytes are already absorbed into deck function
+    /// state, this routine can be used for finalizing the state, so that arbitrary many
+    /// bytes can be squeezed out of deck function state.
+    ///
+    /// - Once finalized, calling this routine again on same object does nothing.
+    /// - Attempting to absorb new message bytes on already finalized state, does nothing.
+    /// - After finalization, one might start squeezing arbitrary many output bytes.
+    /// - After finishing squeezing, when new message arrives, arbitrary many bytes
+    ///   can be consumed into deck function state, by restarting `absorb->finalize->squeeze` cycle.
+    /// - Byte offset must be <= width of permutation, see `finalize_with_offset` otherwise.
+    ///
+    /// This routine implements portion of algorithm 1 of https://ia.cr/2016/1188.
+    #[inline(always)]
+    pub fn finalize(&mut self, domain_seperator: u8, ds_bit_width: usize, offset: usize) {
+        debug_assert!(
+            offset <= P::WIDTH,
+            "Byte offset, considered during squeezing, must be <= {} -bytes",
+            P::WIDTH
+        );
+
+        self.finalize_with_offset(domain_seperator, ds_bit_width, offset as u64);
+    }
+
+    /// Same as `finalize`, but allows skipping arbitrary many ( i.e. not limited to <= width of
+    /// permutation ) bytes of output, before squeezing begins. Skipped output blocks are never
+    /// computed, only output mask is rolled forward.
+    #[inline(always)]
+    pub fn finalize_with_offset(&mut self, domain_seperator: u8, ds_bit_width: usize, offset: u64) {
+        debug_assert!(
+            ds_bit_width <= 7,
+            "Domain seperator bit width is not allowed to be > 7"
+        );
+
+        if self.finalized == usize::MAX {
+            return;
+        }
+
+        let mask = (1u8 << ds_bit_width) - 1u8;
+        let pad_byte = (1u8 << ds_bit_width) | (domain_seperator & mask);
+
+        self.iblk.as_mut()[self.ioff..].fill(0);
+        self.iblk.as_mut()[self.ioff] = pad_byte;
+
+        compress_blocks::<PC, RC>(&mut self.imask, &mut self.acc, self.iblk.as_ref());
+
+        RC::roll(&mut self.imask);
+        self.ibase = self.imask;
+
+        wipe(self.iblk.as_mut());
+        self.ioff = 0;
+        self.finalized = usize::MAX;
+
+        self.obase = self.acc;
+        PD::permute(&mut self.obase);
+
+        self.omask = self.obase;
+        self.oidx = 0;
+        self.oskip = offset;
+
+        self.expand();
+        self.seek_to(offset);
+    }
+
+    /// Given that N -many message bytes are already absorbed into deck function state and
+    /// state is finalized, this routine can be used for squeezing arbitrary many bytes out
+    /// of deck function state. One can call this function arbitrary many times, each time
+    /// requesting arbitrary many bytes, if and only if state is already finalized and it's
+    /// not yet restarted for processing another message using `absorb->finalize->squeeze` cycle.
+    ///
+    /// This routine implements last portion of algorithm 1 of https://ia.cr/2016/1188.
+    #[inline(always)]
+    pub fn squeeze(&mut self, out: &mut [u8]) {
+        if self.finalized != usize::MAX {
+            return;
+        }
+
+        // consume ( rest of ) output block, which is already buffered
+        let read = cmp::min(P::WIDTH - self.ooff, out.len());
+        let (head, out) = out.split_at_mut(read);
+        head.copy_from_slice(&self.oblk.as_ref()[self.ooff..(self.ooff + read)]);
+
+        self.ooff += read;
+        if self.ooff < P::WIDTH {
+            return;
+        }
+
+        let (blks, rm) = out.split_at_mut(out.len() - out.len() % P::WIDTH);
+        expand_blocks::<PE, RE, false>(&mut self.omask, &self.imask, blks);
+
+        self.expand();
+        self.oidx += (blks.len() / P::WIDTH) as u64 + 1;
+
+        rm.copy_from_slice(&self.oblk.as_ref()[..rm.len()]);
+        self.ooff = rm.len();
+    }
+
+    /// Same as `squeeze`, but instead of overwriting `buf`, squeezed bytes are XORed into it,
+    /// which is what a stream cipher does for encrypting/ decrypting in-place. Full output
+    /// blocks are XORed straight into `buf`, without going through internal output buffer.
+    #[inline(always)]
+    pub fn squeeze_xor(&mut self, buf: &mut [u8]) {
+        if self.finalized != usize::MAX {
+            return;
+        }
+
+        // consume ( rest of ) output block, which is already buffered
+        let read = cmp::min(P::WIDTH - self.ooff, buf.len());
+        let (head, buf) = buf.split_at_mut(read);
+        xor_bytes(head, &self.oblk.as_ref()[self.ooff..(self.ooff + read)]);
+
+        self.ooff += read;
+        if self.ooff < P::WIDTH {
+            return;
+        }
+
+        let (blks, rm) = buf.split_at_mut(buf.len() - buf.len() % P::WIDTH);
+        expand_blocks::<PE, RE, true>(&mut self.omask, &self.imask, blks);
+
+        self.expand();
+        self.oidx += (blks.len() / P::WIDTH) as u64 + 1;
+
+        xor_bytes(rm, &self.oblk.as_ref()[..rm.len()]);
+        self.ooff = rm.len();
+    }
+
+    /// Given that deck function state is finalized, this routine skips next `n` bytes of
+    /// output, as if those were squeezed and thrown away. Skipped output blocks are never
+    /// computed, only output mask is rolled forward, which costs O(n / P::WIDTH) invocations of
+    /// RE, unless it overrides `RollingFunction::jump` with something faster.
+    /// Panics if output position overflows `u64`.
+    #[inline(always)]
+    pub fn skip(&mut self, n: u64) {
+        if self.finalized != usize::MAX {
+            return;
+        }
+
+        let pos = self.oidx * P::WIDTH as u64 + self.ooff as u64;
+        let pos = pos.checked_add(n);
+        self.seek_to(pos.expect("Output position must not overflow u64"));
+    }
+
+    /// Given that deck function state is finalized, this routine fills `out` with output bytes
+    /// starting at byte position `pos` ( i.e. same bytes one would get after squeezing `pos`
+    /// -many bytes from freshly finalized state ), allowing random-access reads of output.
+    /// Next call to `squeeze` continues from where this one ends. Seeking costs same as `skip`,
+    /// when moving ahead, otherwise same as skipping `pos` bytes from freshly finalized state.
+    /// Panics if output position, including skipped offset, overflows `u64`.
+    #[inline(always)]
+    pub fn squeeze_at(&mut self, pos: u64, out: &mut [u8]) {
+        if self.finalized != usize::MAX {
+            return;
+        }
+
+        let pos = self.oskip.checked_add(pos);
+        self.seek_to(pos.expect("Output position must not overflow u64"));
+        self.squeeze(out);
+    }
+
+    /// Moves output cursor to absolute byte position `pos` ( counted from very beginning of
+    /// output, including skipped offset ), computing only the output block holding that byte.
+    #[inline(always)]
+    fn seek_to(&mut self, pos: u64) {
+        let blk_idx = pos / P::WIDTH as u64;
+
+        if blk_idx != self.oidx {
+            // `omask` is the mask for output block `oidx + 1`, so it can be rolled
+            // forward when moving ahead, otherwise start rolling from the very first one
+            let rolls = if blk_idx > self.oidx {
+                blk_idx - self.oidx - 1
+            } else {
+                self.omask = self.obase;
+                blk_idx
+            };
+
+            RE::jump(&mut self.omask, rolls);
+
+            self.expand();
+            self.oidx = blk_idx;
+        }
+
+        self.ooff = (pos % P::WIDTH as u64) as usize;
+    }
+
+    /// Computes next output block into output buffer, from current output mask, which is then
+    /// rolled forward.
+    #[inline(always)]
+    pub(crate) fn expand(&mut self) {
+        let mut state = self.omask;
+        PE::permute(&mut state);
+        P::xor(&mut state, &self.imask);
+        P::store(&state, self.oblk.as_mut());
+
+        RE::roll(&mut self.omask);
+    }
+
+    /// Given that a message of arbitrary byte length is absorbed into deck function state and
+    /// it's also finalized i.e. ready to be squeezed, this function can be invoked when you've
+    /// new message waiting to be absorbed into deck function state and you need to restart the
+    /// `absorb->finalize->squeeze` cycle.
+    ///
+    /// Note, if the deck function state is not yet finalized, calling this function should do nothing.
+    /// Remember you're very much allowed to restart `absorb->finalize->squeeze` cycle any number of times
+    /// you want.
+    ///
+    /// This routine implements portion of algorithm 1 of https://ia.cr/2016/1188.
+    #[inline(always)]
+    pub fn restart(&mut self) {
+        if self.finalized != usize::MAX {
+            return;
+        }
+
+        wipe_state(&mut self.omask, P::ZERO);
+        wipe_state(&mut self.obase, P::ZERO);
+        wipe(self.oblk.as_mut());
+        self.ooff = 0;
+        self.oidx = 0;
+        self.oskip = 0;
+        self.finalized = usize::MIN;
+    }
+
+    /// Returns true if deck function state is finalized i.e. it's ready to be squeezed, but
+    /// can't absorb any more message bytes, until `absorb->finalize->squeeze` cycle is restarted.
+    #[inline(always)]
+    pub fn is_finalized(&self) -> bool {
+        self.finalized == usize::MAX
+    }
+}
+
+#[cfg(not(feature = "zeroize"))]
+impl<P: Permutation, RC, RE, PC, PD, PE> Clone for Farfalle<P, RC, RE, PC, PD, PE> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+#[cfg(not(feature = "zeroize"))]
+impl<P: Permutation, RC, RE, PC, PD, PE> Copy for Farfalle<P, RC, RE, PC, PD, PE> {}
+
+#[cfg(feature = "zeroize")]
+impl<P: Permutation, RC, RE, PC, PD, PE> Clone for Farfalle<P, RC, RE, PC, PD, PE> {
+    fn clone(&self) -> Self {
+        Self {
+            imask: self.imask,
+            ibase: self.ibase,
+            omask: self.omask,
+            obase: self.obase,
+            acc: self.acc,
+            iblk: self.iblk,
+            oblk: self.oblk,
+            ioff: self.ioff,
+            ooff: self.ooff,
+            oidx: self.oidx,
+            oskip: self.oskip,
+            finalized: self.finalized,
+            _marker: PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<P: Permutation, RC, RE, PC, PD, PE> Zeroize for Farfalle<P, RC, RE, PC, PD, PE> {
+    fn zeroize(&mut self) {
+        wipe_state(&mut self.imask, P::ZERO);
+        wipe_state(&mut self.ibase, P::ZERO);
+        wipe_state(&mut self.omask, P::ZERO);
+        wipe_state(&mut self.obase, P::ZERO);
+        wipe_state(&mut self.acc, P::ZERO);
+        self.iblk.as_mut().zeroize();
+        self.oblk.as_mut().zeroize();
+        self.ioff.zeroize();
+        self.ooff.zeroize();
+        self.oidx.zeroize();
+        self.oskip.zeroize();
+        self.finalized.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<P: Permutation, RC, RE, PC, PD, PE> Drop for Farfalle<P, RC, RE, PC, PD, PE> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<P: Permutation, RC, RE, PC, PD, PE> ZeroizeOnDrop for Farfalle<P, RC, RE, PC, PD, PE> {}
+
+/// Compresses all full blocks of message `msg` into accumulator `acc`, s.t. first block is masked
+/// using `mask`, which is rolled once after each block i.e. this is the inner loop of `absorb`,
+/// shared among routines absorbing blocks at arbitrary index.
+#[inline(always)]
+pub(crate) fn compress_blocks<P, RC>(mask: &mut P::State, acc: &mut P::State, msg: &[u8])
+where
+    P: Permutation,
+    RC: RollingFunction<P::State>,
+{
+    // groups of blocks are permuted together, when permutation benefits from it
+    let msg = if P::PARALLELISM > 1 {
+        let grp_len = P::PARALLELISM * P::WIDTH;
+        let (grps, rm) = msg.split_at(msg.len() - msg.len() % grp_len);
+
+        for grp in grps.chunks_exact(grp_len) {
+            let mut states = [P::ZERO; MAX_PARALLELISM];
+            let states = &mut states[..P::PARALLELISM];
+
+            for (state, blk) in states.iter_mut().zip(grp.chunks_exact(P::WIDTH)) {
+                *state = P::load(blk);
+                P::xor(state, mask);
+                RC::roll(mask);
+            }
+
+            P::permute_many(states);
+
+            for state in states.iter() {
+                P::xor(acc, state);
+            }
+        }
+
+        rm
+    } else {
+        msg
+    };
+
+    for blk in msg.chunks_exact(P::WIDTH) {
+        let mut state = P::load(blk);
+        P::xor(&mut state, mask);
+        P::permute(&mut state);
+        P::xor(acc, &state);
+
+        RC::roll(mask);
+    }
+}
+
+/// Fills all full blocks of `blks` with output blocks ( or XORs output blocks into them, if `XOR`
+/// is set ), s.t. first one is computed from output mask `mask`, which is rolled once after each
+/// block, and each one is masked using `imask` i.e. this is the inner loop of `squeeze`, shared
+/// among routines squeezing many output blocks at once.
+#[inline(always)]
+pub(crate) fn expand_blocks<P, RE, const XOR: bool>(
+    mask: &mut P::State,
+    imask: &P::State,
+    blks: &mut [u8],
+) where
+    P: Permutation,
+    RE: RollingFunction<P::State>,
+{
+    // groups of blocks are computed together, when permutation benefits from it
+    let blks = if P::PARALLELISM > 1 {
+        let grp_len = P::PARALLELISM * P::WIDTH;
+        let (grps, rm) = blks.split_at_mut(blks.len() - blks.len() % grp_len);
+
+        for grp in grps.chunks_exact_mut(grp_len) {
+            let mut states = [P::ZERO; MAX_PARALLELISM];
+            let states = &mut states[..P::PARALLELISM];
+
+            for state in states.iter_mut() {
+                *state = *mask;
+                RE::roll(mask);
+            }
+
+            P::permute_many(states);
+
+            for (state, blk) in states.iter_mut().zip(grp.chunks_exact_mut(P::WIDTH)) {
+                P::xor(state, imask);
+                if XOR {
+                    P::xor_into(state, blk);
+                } else {
+                    P::store(state, blk);
+                }
+            }
+        }
+
+        rm
+    } else {
+        blks
+    };
+
+    for blk in blks.chunks_exact_mut(P::WIDTH) {
+        let mut state = *mask;
+        P::permute(&mut state);
+        P::xor(&mut state, imask);
+
+        if XOR {
+            P::xor_into(&state, blk);
+        } else {
+            P::store(&state, blk);
+        }
+
+        RE::roll(mask);
+    }
+}
+
+/// Overwrites given permutation state with zero state. When `zeroize` feature is enabled, it's
+/// done in a way that compiler can't optimize away, even if the state is never read again.
+#[inline(always)]
+fn wipe_state<S: Copy>(state: &mut S, zero: S) {
+    #[cfg(feature = "zeroize")]
+    {
+        // SAFETY: `state` is a valid, aligned and exclusive reference
+        unsafe { core::ptr::write_volatile(state, zero) };
+        core::sync::atomic::compiler_fence(core::sync::atomic::Ordering::SeqCst);
+    }
+
+    #[cfg(not(feature = "zeroize"))]
+    {
+        *state = zero;
+    }
+}
+
+/// Xoodoo\[n_r\] permutation, see https://ia.cr/2018/767, s.t. state is 12 lanes, each of 32 -bit
+/// width, loaded from/ stored as bytes, in little-endian order.
+///
+/// When no SIMD implementation of parallel permutation is enabled, ILP ( <= 8 ) message/ output
+/// blocks are permuted together, with their rounds interleaved, for instruction level parallelism.
+/// It defaults to what's best suited for target, see `ILP_WIDTH`.
+#[derive(Clone, Copy, Debug)]
+pub struct Xoodoo<const ROUNDS: usize, const ILP: usize = { ILP_WIDTH }>;
+
+/// \# -of message/ output blocks permuted together, with their rounds interleaved, by default. Two
+/// states ( i.e. 24 words ) fit in general purpose registers of targets such as aarch64 and
+/// RISC-V. Not done on x86/ x86_64, having only 16 of them, where register spills eat up what's
+/// gained, leaving both widths within noise of each other, see `ilp` benchmark in benches/xoofff.rs.
+#[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+pub const ILP_WIDTH: usize = 2;
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+pub const ILP_WIDTH: usize = 1;
+
+impl<const ROUNDS: usize, const ILP: usize> Permutation for Xoodoo<ROUNDS, ILP> {
+    type State = [u32; 12];
+    type Block = [u8; 48];
+
+    const WIDTH: usize = 48;
+    const ZERO: Self::State = [0u32; 12];
+    const ZERO_BLOCK: Self::Block = [0u8; 48];
+
+    // groups of 8 states are permuted together, using 8 -way parallel permutation
+    #[cfg(feature = "intrinsics")]
+    const PARALLELISM: usize = PAR_WIDTH;
+
+    // groups of states are permuted in lockstep, for instruction level parallelism
+    #[cfg(not(feature = "intrinsics"))]
+    const PARALLELISM: usize = ILP;
+
+    #[inline(always)]
+    fn permute(state: &mut Self::State) {
+        const { assert!(ROUNDS <= xoodoo::MAX_ROUNDS, "Xoodoo rounds must be <= 12") };
+        xoodoo::permute_fast::<ROUNDS>(state);
+    }
+
+    #[inline(always)]
+    fn permute_many(states: &mut [Self::State]) {
+        const { assert!(ILP >= 1 && ILP <= MAX_PARALLELISM, "ILP must be in [1, 8]") };
+
+        #[cfg(feature = "intrinsics")]
+        let states = {
+            let mut grps = states.chunks_exact_mut(PAR_WIDTH);
+            for grp in grps.by_ref() {
+                intrinsics::permute_x8::<ROUNDS>(grp.try_into().unwrap());
+            }
+            grps.into_remainder()
+        };
+
+        let states = if ILP > 1 {
+            let mut grps = states.chunks_exact_mut(ILP);
+            for grp in grps.by_ref() {
+                xoodoo::permute_interleaved::<ILP, ROUNDS>(grp.try_into().unwrap());
+            }
+            grps.into_remainder()
+        } else {
+            states
+        };
+
+        states.iter_mut().for_each(Self::permute);
+    }
+
+    #[inline(always)]
+    fn load(bytes: &[u8]) -> Self::State {
+        bytes_to_le_words(bytes.try_into().unwrap())
+    }
+
+    #[inline(always)]
+    fn store(state: &Self::State, bytes: &mut [u8]) {
+        words_to_le_bytes(state, bytes.try_into().unwrap());
+    }
+
+    #[inline(always)]
+    fn xor(dst: &mut Self::State, src: &Self::State) {
+        dst.iter_mut().zip(src.iter()).for_each(|(d, s)| *d ^= *s);
+    }
+
+    #[inline(always)]
+    fn xor_into(state: &Self::State, bytes: &mut [u8]) {
+        xor_le_words(state, bytes.try_into().unwrap());
+    }
+}
+
+/// Input mask rolling function roll_Xc of Xoofff, see section 3 of https://ia.cr/2018/767.
+#[derive(Clone, Copy, Debug)]
+pub struct RollXc;
+
+impl RollingFunction<[u32; 12]> for RollXc {
+    #[inline(always)]
+    fn roll(state: &mut [u32; 12]) {
+        rolling::roll_xc(state);
+    }
+
+    /// Jumps ahead in time ~ logarithmic in `n`, see `rolling::roll_xc_jump`.
+    #[inline(always)]
+    fn jump(state: &mut [u32; 12], n: u64) {
+        rolling::roll_xc_jump(state, n);
+    }
+}
+
+/// State rolling function roll_Xe of Xoofff, see section 3 of https://ia.cr/2018/767.
+#[derive(Clone, Copy, Debug)]
+pub struct RollXe;
+
+impl RollingFunction<[u32; 12]> for RollXe {
+    #[inline(always)]
+    fn roll(state: &mut [u32; 12]) {
+        rolling::roll_xe(state);
+    }
+}
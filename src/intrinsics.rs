@@ -0,0 +1,422 @@
+//! 8 -way parallel Xoodoo\[n_r\] permutation, written with stable `core::arch` x86/ x86_64
+//! intrinsics, so that multi-block compression and expansion of serial Xoofff can make use
+//! of SIMD, without requiring nightly `portable_simd`. Along with it, a single state Xoodoo
+//! permutation, keeping each plane in a 128 -bit register, which speeds up every other call to
+//! `xoodoo::permute` i.e. key setup, finalization and expansion of short messages.
+//!
+//! With `std` feature enabled, best available instruction set ( AVX2, otherwise SSSE3, otherwise
+//! SSE2 ) is detected at runtime, otherwise it's chosen at compile-time, from enabled target
+//! features. On any other target architecture, states are simply permuted one after another.
+
+use crate::xoodoo;
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+use crate::xoodoo::{MAX_ROUNDS, RC};
+
+#[cfg(target_arch = "x86")]
+use core::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use core::arch::x86_64::*;
+
+/// \# -of Xoodoo permutation states, permuted together
+pub(crate) const PAR_WIDTH: usize = 8;
+
+/// Vector of 32 -bit lanes, each belonging to a different Xoodoo permutation state, with
+/// all operations required for applying Xoodoo round function, on each of them.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+trait Lanes: Copy {
+    fn splat(v: u32) -> Self;
+    fn xor(self, other: Self) -> Self;
+    /// Computes `!self & other`.
+    fn andnot(self, other: Self) -> Self;
+    /// Rotates each lane left by `L` bits s.t. `L + R = 32`.
+    fn rotl<const L: i32, const R: i32>(self) -> Self;
+}
+
+/// Eight lanes, living in a 256 -bit AVX2 register.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[derive(Clone, Copy)]
+struct Avx2(__m256i);
+
+/// Four lanes, living in a 128 -bit SSE2 register.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[derive(Clone, Copy)]
+struct Sse2(__m128i);
+
+/// Four lanes, living in a 128 -bit SSE2 register, s.t. byte-wise rotations are done using
+/// SSSE3 byte shuffle, instead of two shifts.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[derive(Clone, Copy)]
+struct Ssse3(__m128i);
+
+// SAFETY ( for all `unsafe` blocks of following three impls ): routines are always inlined
+// into the permutation function, which is compiled with respective target feature enabled and
+// only called after making sure that executing CPU supports it.
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+impl Lanes for Avx2 {
+    #[inline(always)]
+    fn splat(v: u32) -> Self {
+        Self(unsafe { _mm256_set1_epi32(v as i32) })
+    }
+
+    #[inline(always)]
+    fn xor(self, other: Self) -> Self {
+        Self(unsafe { _mm256_xor_si256(self.0, other.0) })
+    }
+
+    #[inline(always)]
+    fn andnot(self, other: Self) -> Self {
+        Self(unsafe { _mm256_andnot_si256(self.0, other.0) })
+    }
+
+    #[inline(always)]
+    fn rotl<const L: i32, const R: i32>(self) -> Self {
+        debug_assert_eq!(L + R, 32);
+        Self(unsafe {
+            _mm256_or_si256(
+                _mm256_slli_epi32::<L>(self.0),
+                _mm256_srli_epi32::<R>(self.0),
+            )
+        })
+    }
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+impl Lanes for Sse2 {
+    #[inline(always)]
+    fn splat(v: u32) -> Self {
+        Self(unsafe { _mm_set1_epi32(v as i32) })
+    }
+
+    #[inline(always)]
+    fn xor(self, other: Self) -> Self {
+        Self(unsafe { _mm_xor_si128(self.0, other.0) })
+    }
+
+    #[inline(always)]
+    fn andnot(self, other: Self) -> Self {
+        Self(unsafe { _mm_andnot_si128(self.0, other.0) })
+    }
+
+    #[inline(always)]
+    fn rotl<const L: i32, const R: i32>(self) -> Self {
+        debug_assert_eq!(L + R, 32);
+        Self(unsafe { _mm_or_si128(_mm_slli_epi32::<L>(self.0), _mm_srli_epi32::<R>(self.0)) })
+    }
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+impl Lanes for Ssse3 {
+    #[inline(always)]
+    fn splat(v: u32) -> Self {
+        Self(Sse2::splat(v).0)
+    }
+
+    #[inline(always)]
+    fn xor(self, other: Self) -> Self {
+        Self(Sse2(self.0).xor(Sse2(other.0)).0)
+    }
+
+    #[inline(always)]
+    fn andnot(self, other: Self) -> Self {
+        Self(Sse2(self.0).andnot(Sse2(other.0)).0)
+    }
+
+    #[inline(always)]
+    fn rotl<const L: i32, const R: i32>(self) -> Self {
+        if L == 8 {
+            // i -th byte of each little-endian lane is moved to (i + 1) % 4 -th position
+            Self(unsafe {
+                _mm_shuffle_epi8(
+                    self.0,
+                    _mm_set_epi8(14, 13, 12, 15, 10, 9, 8, 11, 6, 5, 4, 7, 2, 1, 0, 3),
+                )
+            })
+        } else {
+            Self(Sse2(self.0).rotl::<L, R>().0)
+        }
+    }
+}
+
+/// Xoodoo\[n_r\] permutation, applied on each of the states living in lanes of given vectors,
+/// following algorithm 1 of https://ia.cr/2018/767. Note, cyclic shifts of planes, along x
+/// -axis, are simply reordering of vectors, only shifts along z -axis need lane rotations.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[inline(always)]
+fn permutex<V: Lanes, const ROUNDS: usize>(s: &mut [V; 12]) {
+    const { assert!(ROUNDS <= MAX_ROUNDS, "Xoodoo rounds must be <= 12") };
+
+    for &rc in RC.iter().skip(MAX_ROUNDS - ROUNDS) {
+        // θ
+        let p = [
+            s[0].xor(s[4]).xor(s[8]),
+            s[1].xor(s[5]).xor(s[9]),
+            s[2].xor(s[6]).xor(s[10]),
+            s[3].xor(s[7]).xor(s[11]),
+        ];
+        for i in 0..4 {
+            let q = p[(i + 3) & 3];
+            let e = q.rotl::<5, 27>().xor(q.rotl::<14, 18>());
+
+            s[i] = s[i].xor(e);
+            s[4 + i] = s[4 + i].xor(e);
+            s[8 + i] = s[8 + i].xor(e);
+        }
+
+        // ρ_west
+        let a1 = [s[7], s[4], s[5], s[6]];
+        s[4..8].copy_from_slice(&a1);
+        for v in s[8..12].iter_mut() {
+            *v = v.rotl::<11, 21>();
+        }
+
+        // ι
+        s[0] = s[0].xor(V::splat(rc));
+
+        // χ
+        for i in 0..4 {
+            let b0 = s[4 + i].andnot(s[8 + i]);
+            let b1 = s[8 + i].andnot(s[i]);
+            let b2 = s[i].andnot(s[4 + i]);
+
+            s[i] = s[i].xor(b0);
+            s[4 + i] = s[4 + i].xor(b1);
+            s[8 + i] = s[8 + i].xor(b2);
+        }
+
+        // ρ_east
+        for v in s[4..8].iter_mut() {
+            *v = v.rotl::<1, 31>();
+        }
+        let a2 = [s[10], s[11], s[8], s[9]];
+        for i in 0..4 {
+            s[8 + i] = a2[i].rotl::<8, 24>();
+        }
+    }
+}
+
+/// Permutes 8 states, laid out in lanes of AVX2 registers.
+///
+/// # Safety
+///
+/// Executing CPU must support AVX2.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[target_feature(enable = "avx2")]
+pub(crate) unsafe fn permute_avx2<const ROUNDS: usize>(states: &mut [[u32; 12]; PAR_WIDTH]) {
+    let mut s = [Avx2(_mm256_setzero_si256()); 12];
+
+    for (j, v) in s.iter_mut().enumerate() {
+        let lanes: [u32; 8] = core::array::from_fn(|i| states[i][j]);
+        v.0 = unsafe { _mm256_loadu_si256(lanes.as_ptr() as *const __m256i) };
+    }
+
+    permutex::<Avx2, ROUNDS>(&mut s);
+
+    for (j, v) in s.iter().enumerate() {
+        let mut lanes = [0u32; 8];
+        unsafe { _mm256_storeu_si256(lanes.as_mut_ptr() as *mut __m256i, v.0) };
+
+        for (state, lane) in states.iter_mut().zip(lanes) {
+            state[j] = lane;
+        }
+    }
+}
+
+/// Permutes 8 states, laid out in lanes of two sets of 128 -bit registers.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[inline(always)]
+fn permute_x128<V: Lanes, const ROUNDS: usize>(
+    states: &mut [[u32; 12]; PAR_WIDTH],
+    wrap: fn(__m128i) -> V,
+    unwrap: fn(V) -> __m128i,
+) {
+    for half in states.chunks_exact_mut(4) {
+        let mut s = [V::splat(0); 12];
+
+        for (j, v) in s.iter_mut().enumerate() {
+            let lanes: [u32; 4] = core::array::from_fn(|i| half[i][j]);
+            *v = wrap(unsafe { _mm_loadu_si128(lanes.as_ptr() as *const __m128i) });
+        }
+
+        permutex::<V, ROUNDS>(&mut s);
+
+        for (j, v) in s.iter().enumerate() {
+            let mut lanes = [0u32; 4];
+            unsafe { _mm_storeu_si128(lanes.as_mut_ptr() as *mut __m128i, unwrap(*v)) };
+
+            for (state, lane) in half.iter_mut().zip(lanes) {
+                state[j] = lane;
+            }
+        }
+    }
+}
+
+/// Permutes 8 states, four at a time, laid out in lanes of SSSE3 registers.
+///
+/// # Safety
+///
+/// Executing CPU must support SSSE3.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[target_feature(enable = "ssse3")]
+pub(crate) unsafe fn permute_ssse3<const ROUNDS: usize>(states: &mut [[u32; 12]; PAR_WIDTH]) {
+    permute_x128::<Ssse3, ROUNDS>(states, Ssse3, |v| v.0)
+}
+
+/// Permutes 8 states, four at a time, laid out in lanes of SSE2 registers.
+///
+/// # Safety
+///
+/// Executing CPU must support SSE2.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[target_feature(enable = "sse2")]
+pub(crate) unsafe fn permute_sse2<const ROUNDS: usize>(states: &mut [[u32; 12]; PAR_WIDTH]) {
+    permute_x128::<Sse2, ROUNDS>(states, Sse2, |v| v.0)
+}
+
+/// Applies Xoodoo\[n_r\] permutation on each of 8 independent states, using best instruction
+/// set, supported by executing CPU.
+#[inline(always)]
+pub(crate) fn permute_x8<const ROUNDS: usize>(states: &mut [[u32; 12]; PAR_WIDTH]) {
+    #[cfg(all(any(target_arch = "x86", target_arch = "x86_64"), feature = "std"))]
+    {
+        // SAFETY: each routine is called only after detecting that CPU supports required feature
+        if std::is_x86_feature_detected!("avx2") {
+            return unsafe { permute_avx2::<ROUNDS>(states) };
+        }
+        if std::is_x86_feature_detected!("ssse3") {
+            return unsafe { permute_ssse3::<ROUNDS>(states) };
+        }
+        if std::is_x86_feature_detected!("sse2") {
+            return unsafe { permute_sse2::<ROUNDS>(states) };
+        }
+    }
+
+    #[cfg(all(any(target_arch = "x86", target_arch = "x86_64"), not(feature = "std")))]
+    {
+        // SAFETY: target feature is enabled at compile-time
+        if cfg!(target_feature = "avx2") {
+            return unsafe { permute_avx2::<ROUNDS>(states) };
+        }
+        if cfg!(target_feature = "ssse3") {
+            return unsafe { permute_ssse3::<ROUNDS>(states) };
+        }
+        if cfg!(target_feature = "sse2") {
+            return unsafe { permute_sse2::<ROUNDS>(states) };
+        }
+    }
+
+    for state in states.iter_mut() {
+        xoodoo::permute::<ROUNDS>(state);
+    }
+}
+
+/// Xoodoo\[n_r\] permutation of a single state, s.t. each plane lives in a 128 -bit register,
+/// following https://github.com/XKCP/XKCP/blob/master/lib/low/Xoodoo/SSE2/Xoodoo-SSE2.c. Cyclic
+/// shifts of planes, along x -axis, are lane shuffles, while ρ_east's plane shift and rotation of
+/// last plane is a single byte shuffle, which is why SSSE3 is required.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[inline(always)]
+unsafe fn permute_xmm<const ROUNDS: usize>(state: &mut [u32; 12]) {
+    const { assert!(ROUNDS <= MAX_ROUNDS, "Xoodoo rounds must be <= 12") };
+
+    /// Rotates each 32 -bit lane of `v` left by `L` bits s.t. `L + R = 32`.
+    #[inline(always)]
+    unsafe fn rotl<const L: i32, const R: i32>(v: __m128i) -> __m128i {
+        _mm_or_si128(_mm_slli_epi32::<L>(v), _mm_srli_epi32::<R>(v))
+    }
+
+    // i -th lane of output is (i + 2) % 4 -th lane of input, rotated left by 8 bits
+    let rho_east_2 = _mm_set_epi32(0x0605_0407, 0x0201_0003, 0x0e0d_0c0f, 0x0a09_080b);
+
+    let mut a = _mm_loadu_si128(state.as_ptr() as *const __m128i);
+    let mut b = _mm_loadu_si128(state.as_ptr().add(4) as *const __m128i);
+    let mut c = _mm_loadu_si128(state.as_ptr().add(8) as *const __m128i);
+
+    for &rc in RC.iter().skip(MAX_ROUNDS - ROUNDS) {
+        // θ
+        let p = _mm_shuffle_epi32::<0x93>(_mm_xor_si128(_mm_xor_si128(a, b), c));
+        let e = _mm_xor_si128(rotl::<5, 27>(p), rotl::<14, 18>(p));
+        a = _mm_xor_si128(a, e);
+        b = _mm_xor_si128(b, e);
+        c = _mm_xor_si128(c, e);
+
+        // ρ_west
+        b = _mm_shuffle_epi32::<0x93>(b);
+        c = rotl::<11, 21>(c);
+
+        // ι
+        a = _mm_xor_si128(a, _mm_set_epi32(0, 0, 0, rc as i32));
+
+        // χ, updating planes one after another, which is fine, because !c & (a ^ (!b & c)) is
+        // same as !c & a, and so on
+        a = _mm_xor_si128(a, _mm_andnot_si128(b, c));
+        b = _mm_xor_si128(b, _mm_andnot_si128(c, a));
+        c = _mm_xor_si128(c, _mm_andnot_si128(a, b));
+
+        // ρ_east
+        b = rotl::<1, 31>(b);
+        c = _mm_shuffle_epi8(c, rho_east_2);
+    }
+
+    _mm_storeu_si128(state.as_mut_ptr() as *mut __m128i, a);
+    _mm_storeu_si128(state.as_mut_ptr().add(4) as *mut __m128i, b);
+    _mm_storeu_si128(state.as_mut_ptr().add(8) as *mut __m128i, c);
+}
+
+/// Permutes a single state, keeping its planes in SSSE3 registers.
+///
+/// # Safety
+///
+/// Executing CPU must support SSSE3.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[target_feature(enable = "ssse3")]
+pub(crate) unsafe fn permute_x1_ssse3<const ROUNDS: usize>(state: &mut [u32; 12]) {
+    unsafe { permute_xmm::<ROUNDS>(state) }
+}
+
+/// Permutes a single state, keeping its planes in 128 -bit registers, using VEX encoded ( i.e.
+/// non-destructive, three operand ) AVX instructions, which saves register to register moves.
+///
+/// # Safety
+///
+/// Executing CPU must support AVX.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[target_feature(enable = "avx")]
+pub(crate) unsafe fn permute_x1_avx<const ROUNDS: usize>(state: &mut [u32; 12]) {
+    unsafe { permute_xmm::<ROUNDS>(state) }
+}
+
+/// Applies Xoodoo\[n_r\] permutation on a single state, using best instruction set, supported
+/// by executing CPU. Returns false, leaving state untouched, if neither AVX nor SSSE3 is
+/// available, so that caller can fall back to scalar implementation.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[inline(always)]
+pub(crate) fn permute_x1<const ROUNDS: usize>(state: &mut [u32; 12]) -> bool {
+    #[cfg(feature = "std")]
+    {
+        // SAFETY: each routine is called only after detecting that CPU supports required feature
+        if std::is_x86_feature_detected!("avx") {
+            unsafe { permute_x1_avx::<ROUNDS>(state) };
+            return true;
+        }
+        if std::is_x86_feature_detected!("ssse3") {
+            unsafe { permute_x1_ssse3::<ROUNDS>(state) };
+            return true;
+        }
+    }
+
+    #[cfg(not(feature = "std"))]
+    {
+        // SAFETY: target feature is enabled at compile-time
+        if cfg!(target_feature = "avx") {
+            unsafe { permute_x1_avx::<ROUNDS>(state) };
+            return true;
+        }
+        if cfg!(target_feature = "ssse3") {
+            unsafe { permute_x1_ssse3::<ROUNDS>(state) };
+            return true;
+        }
+    }
+
+    false
+}
@@ -0,0 +1,126 @@
+use crate::xoofff::wipe;
+use crate::Xoofff;
+use std::io::{self, Read, Write};
+
+/// Byte length of internal buffer, used when absorbing from a reader or squeezing into a writer.
+/// It's a multiple of byte width of parallel permutation of all SIMD backends ( i.e. 4/ 8/ 16
+/// Xoodoo states ), so that each buffer worth of bytes is processed without partial staging.
+pub const IO_BUF_SIZE: usize = 48 * 16 * 8;
+
+/// Absorbing adapter over Xoofff deck function, implementing `std::io::Write`, so that
+/// message bytes can be piped into deck function state from anywhere e.g. using `std::io::copy`.
+///
+/// Writing into an already finalized deck function state results in an error.
+pub struct Absorber<'a> {
+    deck: &'a mut Xoofff,
+}
+
+/// Squeezing adapter over Xoofff deck function, implementing `std::io::Read`, so that
+/// key-stream/ XOF output can be piped from deck function state to anywhere.
+///
+/// Reading from a deck function state which is not yet finalized results in an error.
+pub struct Squeezer<'a> {
+    deck: &'a mut Xoofff,
+}
+
+impl Write for Absorber<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.deck.is_finalized() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "can't absorb into finalized Xoofff state",
+            ));
+        }
+
+        self.deck.absorb(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Read for Squeezer<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if !self.deck.is_finalized() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "can't squeeze from non-finalized Xoofff state",
+            ));
+        }
+
+        self.deck.squeeze(buf);
+        Ok(buf.len())
+    }
+}
+
+impl Xoofff {
+    /// Returns an adapter, implementing `std::io::Write`, which absorbs all bytes written into it.
+    pub fn absorber(&mut self) -> Absorber<'_> {
+        Absorber { deck: self }
+    }
+
+    /// Returns an adapter, implementing `std::io::Read`, which squeezes bytes out of deck function
+    /// state, when read from. Deck function state must already be finalized.
+    pub fn squeezer(&mut self) -> Squeezer<'_> {
+        Squeezer { deck: self }
+    }
+
+    /// Absorbs all bytes, that can be read from `reader` until it reaches EOF, into deck function
+    /// state, returning number of absorbed bytes. Bytes are read in chunks of `IO_BUF_SIZE`.
+    pub fn absorb_reader<R: Read>(&mut self, mut reader: R) -> io::Result<u64> {
+        if self.is_finalized() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "can't absorb into finalized Xoofff state",
+            ));
+        }
+
+        let mut buf = [0u8; IO_BUF_SIZE];
+        let mut total = 0u64;
+
+        loop {
+            let n = match reader.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+
+            self.absorb(&buf[..n]);
+            total += n as u64;
+        }
+
+        Ok(total)
+    }
+
+    /// Squeezes `n` bytes out of ( already finalized ) deck function state, writing all of them
+    /// into `writer`. Bytes are squeezed in chunks of `IO_BUF_SIZE`.
+    pub fn squeeze_to_writer<W: Write>(&mut self, mut writer: W, n: u64) -> io::Result<()> {
+        if !self.is_finalized() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "can't squeeze from non-finalized Xoofff state",
+            ));
+        }
+
+        let mut buf = [0u8; IO_BUF_SIZE];
+        let mut left = n;
+
+        while left > 0 {
+            let len = left.min(IO_BUF_SIZE as u64) as usize;
+
+            self.squeeze(&mut buf[..len]);
+            let res = writer.write_all(&buf[..len]);
+            if res.is_err() {
+                wipe(&mut buf);
+                return res;
+            }
+            left -= len as u64;
+        }
+
+        wipe(&mut buf);
+        Ok(())
+    }
+}
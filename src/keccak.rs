@@ -0,0 +1,111 @@
+/// Maximum number of rounds one can request to have when applying Keccak-p\[1600, n_r\] permutation
+/// i.e. n_r <= MAX_ROUNDS, which is Keccak-f\[1600\] itself.
+///
+/// See section 3.4 of https://doi.org/10.6028/NIST.FIPS.202
+pub(crate) const MAX_ROUNDS: usize = 24;
+
+/// Keccak-p\[1600, n_r\] round constants, see algorithm 5 and 6 of https://doi.org/10.6028/NIST.FIPS.202
+const RC: [u64; MAX_ROUNDS] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+/// Rotation offsets of ρ step mapping, s.t. lane (x, y) lives at index x + 5 * y, see table 2 of
+/// https://doi.org/10.6028/NIST.FIPS.202
+const RHO: [u32; 25] = [
+    0, 1, 62, 28, 27, 36, 44, 6, 55, 20, 3, 10, 43, 25, 39, 41, 45, 15, 21, 8, 18, 2, 61, 56, 14,
+];
+
+/// θ step mapping of Keccak-p permutation, as described in algorithm 1 of https://doi.org/10.6028/NIST.FIPS.202
+#[inline(always)]
+fn theta(state: &mut [u64; 25]) {
+    let mut c = [0u64; 5];
+    for (x, c) in c.iter_mut().enumerate() {
+        *c = state[x] ^ state[x + 5] ^ state[x + 10] ^ state[x + 15] ^ state[x + 20];
+    }
+
+    for x in 0..5 {
+        let d = c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1);
+        for y in 0..5 {
+            state[x + 5 * y] ^= d;
+        }
+    }
+}
+
+/// ρ and π step mappings of Keccak-p permutation, applied together, as described in algorithm 2
+/// and 3 of https://doi.org/10.6028/NIST.FIPS.202 s.t. lane (x, y) is rotated and moved to (y, 2x + 3y).
+#[inline(always)]
+fn rho_pi(state: &mut [u64; 25]) {
+    let mut tmp = [0u64; 25];
+    for x in 0..5 {
+        for y in 0..5 {
+            let idx = x + 5 * y;
+            tmp[y + 5 * ((2 * x + 3 * y) % 5)] = state[idx].rotate_left(RHO[idx]);
+        }
+    }
+    *state = tmp;
+}
+
+/// χ step mapping of Keccak-p permutation, as described in algorithm 4 of https://doi.org/10.6028/NIST.FIPS.202
+#[inline(always)]
+fn chi(state: &mut [u64; 25]) {
+    for y in 0..5 {
+        let mut plane = [0u64; 5];
+        plane.copy_from_slice(&state[5 * y..5 * (y + 1)]);
+
+        for x in 0..5 {
+            state[x + 5 * y] = plane[x] ^ (!plane[(x + 1) % 5] & plane[(x + 2) % 5]);
+        }
+    }
+}
+
+/// ι step mapping of Keccak-p permutation, as described in algorithm 6 of https://doi.org/10.6028/NIST.FIPS.202
+#[inline(always)]
+fn iota(state: &mut [u64; 25], ridx: usize) {
+    state[0] ^= RC[ridx];
+}
+
+/// Round function of Keccak-p permutation, as described in section 3.3 of https://doi.org/10.6028/NIST.FIPS.202
+#[inline(always)]
+fn round(state: &mut [u64; 25], ridx: usize) {
+    debug_assert!(ridx < MAX_ROUNDS, "Round index must ∈ [0, MAX_ROUNDS) !");
+
+    theta(state);
+    rho_pi(state);
+    chi(state);
+    iota(state, ridx);
+}
+
+/// Keccak-p\[1600, n_r\] permutation function s.t. last n_r ( <= MAX_ROUNDS ) rounds of
+/// Keccak-f\[1600\] are applied on permutation state, as described in algorithm 7 of
+/// https://doi.org/10.6028/NIST.FIPS.202
+#[inline(always)]
+pub fn permute<const ROUNDS: usize>(state: &mut [u64; 25]) {
+    const { assert!(ROUNDS <= MAX_ROUNDS, "Keccak-p rounds must be <= 24") };
+
+    for ridx in (MAX_ROUNDS - ROUNDS)..MAX_ROUNDS {
+        round(state, ridx);
+    }
+}
@@ -0,0 +1,122 @@
+//! Kravatte deck function, obtained by instantiating Farfalle construction with Keccak-p\[1600, 6\]
+//! permutation, see section 7 of https://ia.cr/2016/1188.
+
+use crate::farfalle::{Farfalle, Permutation, RollingFunction};
+use crate::keccak;
+
+/// Keccak-p\[1600, n_r\] being a 1600 -bit permutation, messages are consumed in 200 -bytes chunks
+const BLOCK_SIZE: usize = 200;
+
+/// \# -of lanes ( each of 64 -bit width ) in Keccak-p\[1600, n_r\] permutation state
+const LANE_CNT: usize = BLOCK_SIZE / core::mem::size_of::<u64>();
+
+/// \# -of rounds of Keccak-p\[1600, n_r\] permutation, used in all phases of Kravatte Achouffe
+const ROUNDS: usize = 6;
+
+/// Kravatte is a deck function, obtained by instantiating Farfalle construction with
+/// Keccak-p\[1600, 6\] permutation and two rolling functions roll_c ( linear ) and roll_e
+/// ( non-linear ), as defined for Kravatte Achouffe. It offers same API as `crate::Xoofff`
+/// i.e. incremental `absorb->finalize->squeeze` cycles, restartable any number of times,
+/// while consuming messages and producing output in 200 -bytes blocks.
+///
+/// Key must be of byte length < 200.
+///
+/// See https://ia.cr/2016/1188 for definition of Farfalle and Kravatte.
+pub type Kravatte = Farfalle<KeccakP1600<ROUNDS>, RollC, RollE>;
+
+/// Keccak-p\[1600, n_r\] permutation, see https://doi.org/10.6028/NIST.FIPS.202, s.t. state is 25
+/// lanes, each of 64 -bit width, loaded from/ stored as bytes, in little-endian order.
+#[derive(Clone, Copy, Debug)]
+pub struct KeccakP1600<const ROUNDS: usize>;
+
+impl<const ROUNDS: usize> Permutation for KeccakP1600<ROUNDS> {
+    type State = [u64; LANE_CNT];
+    type Block = [u8; BLOCK_SIZE];
+
+    const WIDTH: usize = BLOCK_SIZE;
+    const ZERO: Self::State = [0u64; LANE_CNT];
+    const ZERO_BLOCK: Self::Block = [0u8; BLOCK_SIZE];
+
+    #[inline(always)]
+    fn permute(state: &mut Self::State) {
+        keccak::permute::<ROUNDS>(state);
+    }
+
+    #[inline(always)]
+    fn load(bytes: &[u8]) -> Self::State {
+        let mut state = [0u64; LANE_CNT];
+        for (lane, b) in state.iter_mut().zip(bytes.chunks_exact(8)) {
+            *lane = u64::from_le_bytes(b.try_into().unwrap());
+        }
+        state
+    }
+
+    #[inline(always)]
+    fn store(state: &Self::State, bytes: &mut [u8]) {
+        for (lane, b) in state.iter().zip(bytes.chunks_exact_mut(8)) {
+            b.copy_from_slice(&lane.to_le_bytes());
+        }
+    }
+
+    #[inline(always)]
+    fn xor(dst: &mut Self::State, src: &Self::State) {
+        dst.iter_mut().zip(src.iter()).for_each(|(d, s)| *d ^= *s);
+    }
+
+    #[inline(always)]
+    fn xor_into(state: &Self::State, bytes: &mut [u8]) {
+        for (lane, b) in state.iter().zip(bytes.chunks_exact_mut(8)) {
+            let word = u64::from_le_bytes((&*b).try_into().unwrap()) ^ lane;
+            b.copy_from_slice(&word.to_le_bytes());
+        }
+    }
+}
+
+/// Input mask rolling function roll_c of Kravatte Achouffe, which updates only last plane ( i.e.
+/// 5 lanes ) of Keccak-p\[1600\] permutation state s.t. first lane is replaced by
+/// (x_0 <<< 7) ^ x_1 ^ (x_1 >> 3) and lanes are then cyclically shifted by one, see section
+/// 7 of https://ia.cr/2016/1188.
+#[derive(Clone, Copy, Debug)]
+pub struct RollC;
+
+impl RollingFunction<[u64; LANE_CNT]> for RollC {
+    #[inline(always)]
+    fn roll(state: &mut [u64; LANE_CNT]) {
+        roll_c(state);
+    }
+}
+
+/// Output mask rolling function roll_e of Kravatte Achouffe, which updates only last two planes
+/// ( i.e. 10 lanes ) of Keccak-p\[1600\] permutation state s.t. first lane is replaced by
+/// (x_0 <<< 7) ^ (x_1 <<< 18) ^ (x_2 & (x_1 >> 1)) and lanes are then cyclically shifted by one,
+/// see section 7 of https://ia.cr/2016/1188.
+#[derive(Clone, Copy, Debug)]
+pub struct RollE;
+
+impl RollingFunction<[u64; LANE_CNT]> for RollE {
+    #[inline(always)]
+    fn roll(state: &mut [u64; LANE_CNT]) {
+        roll_e(state);
+    }
+}
+
+/// Input mask rolling function roll_c of Kravatte Achouffe, see `RollC`.
+#[inline(always)]
+pub(crate) fn roll_c(state: &mut [u64; LANE_CNT]) {
+    let x0 = state[20];
+    let x1 = state[21];
+
+    state.copy_within(21..25, 20);
+    state[24] = x0.rotate_left(7) ^ x1 ^ (x1 >> 3);
+}
+
+/// Output mask rolling function roll_e of Kravatte Achouffe, see `RollE`.
+#[inline(always)]
+pub(crate) fn roll_e(state: &mut [u64; LANE_CNT]) {
+    let x0 = state[15];
+    let x1 = state[16];
+    let x2 = state[17];
+
+    state.copy_within(16..25, 15);
+    state[24] = x0.rotate_left(7) ^ x1.rotate_left(18) ^ (x2 & (x1 >> 1));
+}
@@ -1,6 +1,13 @@
+#![cfg_attr(not(feature = "std"), no_std)]
 #![cfg_attr(feature = "simd", feature(portable_simd))]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(test)]
+extern crate std;
+
 mod rolling;
+mod snapshot;
 
 #[cfg(feature = "dev")]
 pub mod xoodoo;
@@ -10,14 +17,56 @@ mod xoodoo;
 #[allow(unused)]
 mod xoofff;
 
+pub mod farfalle;
+
+mod keccak;
+
+pub mod kravatte;
+
+pub use crate::kravatte::Kravatte;
+
+mod snp;
+
+pub use crate::snp::XoodooState;
+
+#[cfg(feature = "intrinsics")]
+mod intrinsics;
+
 #[cfg(not(feature = "simd"))]
 pub use crate::xoofff::Xoofff;
 
+pub use crate::xoofff::xoofff_const;
+
+pub use crate::xoofff::XoofffParams;
+
+#[cfg(not(feature = "simd"))]
+pub use crate::snapshot::{StateError, STATE_LEN, TAG_LEN};
+
 #[cfg(feature = "simd")]
 mod simd;
 
 #[cfg(feature = "simd")]
 pub use crate::simd::Xoofff;
 
+#[cfg(all(
+    feature = "simd",
+    feature = "std",
+    any(target_arch = "x86", target_arch = "x86_64")
+))]
+pub use crate::simd::Backend;
+
+mod batch;
+
+pub mod compact;
+
+#[cfg(feature = "std")]
+mod io;
+
+#[cfg(feature = "std")]
+pub use crate::io::{Absorber, Squeezer, IO_BUF_SIZE};
+
+#[cfg(any(feature = "tokio", feature = "futures-io"))]
+pub mod async_io;
+
 #[cfg(test)]
 mod tests;
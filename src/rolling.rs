@@ -1,23 +1,25 @@
-use super::xoodoo::cyclic_shift;
+use super::xoodoo::{cyclic_shift, plane, set_plane};
 
 /// Input mask rolling function roll_Xc, updating the Xoodoo permutation state, as
 /// described in section 3 of https://ia.cr/2018/767
-pub fn roll_xc(state: &mut [u32]) {
+#[inline(always)]
+pub const fn roll_xc(state: &mut [u32]) {
     debug_assert!(
         state.len() == 12,
         "Xoodoo permutation state must have 12 lanes !"
     );
 
     state[0] ^= (state[0] << 13) ^ state[4].rotate_left(3);
-    let b = cyclic_shift::<3, 0>(&state[..4]);
+    let b = cyclic_shift::<3, 0>(&plane(state, 0));
 
-    state.copy_within(4..12, 0);
-    state[8..12].copy_from_slice(&b);
+    shift_planes(state);
+    set_plane(state, 2, &b);
 }
 
 /// State rolling function roll_Xe, updating Xoodoo permutation state, as described
 /// in section 3 of https://ia.cr/2018/767
-pub fn roll_xe(state: &mut [u32]) {
+#[inline(always)]
+pub const fn roll_xe(state: &mut [u32]) {
     debug_assert!(
         state.len() == 12,
         "Xoodoo permutation state must have 12 lanes !"
@@ -25,8 +27,131 @@ pub fn roll_xe(state: &mut [u32]) {
 
     let tmp = state[4] & state[8];
     state[0] = tmp ^ state[0].rotate_left(5) ^ state[4].rotate_left(13) ^ 0x00000007u32;
-    let b = cyclic_shift::<3, 0>(&state[..4]);
+    let b = cyclic_shift::<3, 0>(&plane(state, 0));
 
-    state.copy_within(4..12, 0);
-    state[8..12].copy_from_slice(&b);
+    shift_planes(state);
+    set_plane(state, 2, &b);
+}
+
+/// Moves last two planes of Xoodoo permutation state one plane up, same as
+/// `state.copy_within(4..12, 0)`, but usable in const contexts.
+#[inline(always)]
+const fn shift_planes(state: &mut [u32]) {
+    let p1 = plane(state, 1);
+    let p2 = plane(state, 2);
+
+    set_plane(state, 0, &p1);
+    set_plane(state, 1, &p2);
+}
+
+/// Degree of minimal polynomial m(x) of roll_Xc, seen as a linear map over GF(2)^384.
+const ROLL_XC_DEGREE: usize = 384;
+
+/// Exponents of all non-zero terms of minimal polynomial m(x) of roll_Xc, except the leading
+/// one i.e. m(x) = x^384 + sum(x^t for t in ROLL_XC_TAPS), so that x^384 = sum(x^t) mod m(x).
+const ROLL_XC_TAPS: [usize; 30] = [
+    0, 2, 4, 6, 17, 19, 32, 41, 43, 50, 54, 65, 89, 98, 100, 113, 115, 137, 139, 146, 161, 185,
+    194, 196, 198, 242, 246, 290, 292, 338,
+];
+
+/// Interleaves bits of a 32 -bit word with zeros, which is squaring of a polynomial over GF(2).
+#[inline(always)]
+fn spread_bits(x: u32) -> u64 {
+    let mut x = x as u64;
+
+    x = (x | (x << 16)) & 0x0000ffff0000ffff;
+    x = (x | (x << 8)) & 0x00ff00ff00ff00ff;
+    x = (x | (x << 4)) & 0x0f0f0f0f0f0f0f0f;
+    x = (x | (x << 2)) & 0x3333333333333333;
+    x = (x | (x << 1)) & 0x5555555555555555;
+    x
+}
+
+/// Reduces a polynomial over GF(2) of degree < 768 modulo minimal polynomial of roll_Xc.
+fn reduce(mut poly: [u64; 12]) -> [u64; 6] {
+    for w in (6..12).rev() {
+        // reducing a word may feed few bits back into itself, as highest tap is 338 > 320
+        while poly[w] != 0 {
+            let h = poly[w];
+            poly[w] = 0;
+
+            for t in ROLL_XC_TAPS {
+                let pos = 64 * (w - 6) + t;
+                let (idx, sft) = (pos / 64, pos % 64);
+
+                poly[idx] ^= h << sft;
+                if sft > 0 {
+                    poly[idx + 1] ^= h >> (64 - sft);
+                }
+            }
+        }
+    }
+
+    poly[..6].try_into().unwrap()
+}
+
+/// Computes r(x) = x^n mod m(x), where m(x) is minimal polynomial of roll_Xc, using
+/// left-to-right square-and-multiply.
+fn roll_xc_power(n: u64) -> [u64; 6] {
+    let mut r = [0u64; 6];
+    r[0] = 1;
+
+    for bit in (0..(u64::BITS - n.leading_zeros())).rev() {
+        let mut sq = [0u64; 12];
+        for i in 0..6 {
+            sq[2 * i] = spread_bits(r[i] as u32);
+            sq[2 * i + 1] = spread_bits((r[i] >> 32) as u32);
+        }
+        r = reduce(sq);
+
+        if (n >> bit) & 1 == 1 {
+            let carry = r[5] >> 63;
+            for i in (1..6).rev() {
+                r[i] = (r[i] << 1) | (r[i - 1] >> 63);
+            }
+            r[0] <<= 1;
+
+            if carry == 1 {
+                for t in ROLL_XC_TAPS {
+                    r[t / 64] ^= 1u64 << (t % 64);
+                }
+            }
+        }
+    }
+
+    r
+}
+
+/// Updates Xoodoo permutation state, as if roll_Xc was applied on it `n` times, without
+/// actually applying it `n` times. As roll_Xc is linear over GF(2), roll_Xc^n = r(roll_Xc),
+/// where r(x) = x^n mod m(x) and m(x) is minimal polynomial ( of degree 384 ) of roll_Xc,
+/// so that cost of jumping ahead is ~ logarithmic in `n`, bounded by 384 applications of
+/// roll_Xc. For small `n`, state is simply rolled `n` times.
+pub fn roll_xc_jump(state: &mut [u32], n: u64) {
+    debug_assert!(
+        state.len() == 12,
+        "Xoodoo permutation state must have 12 lanes !"
+    );
+
+    if n < ROLL_XC_DEGREE as u64 {
+        for _ in 0..n {
+            roll_xc(state);
+        }
+        return;
+    }
+
+    let r = roll_xc_power(n);
+
+    // Horner's rule, evaluating r(roll_Xc) on given state
+    let mut acc = [0u32; 12];
+    for j in (0..ROLL_XC_DEGREE).rev() {
+        roll_xc(&mut acc);
+        if (r[j / 64] >> (j % 64)) & 1 == 1 {
+            for (a, s) in acc.iter_mut().zip(state.iter()) {
+                *a ^= *s;
+            }
+        }
+    }
+
+    state.copy_from_slice(&acc);
 }
@@ -0,0 +1,306 @@
+use super::{x16, x4, x8};
+use crate::xoofff as serial;
+#[cfg(feature = "zeroize")]
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// Generates thin wrappers over all public routines of a SIMD backend, each compiled with given
+/// target feature enabled, so that inlined backend routine gets to use wide vector registers,
+/// even though the crate itself is compiled for baseline x86 target.
+macro_rules! backend_fns {
+    ($feature:literal, $deck:ty, $lanes:literal) => {
+        #[target_feature(enable = $feature)]
+        pub(super) fn new(key: &[u8]) -> $deck {
+            <$deck>::new(key)
+        }
+
+        #[target_feature(enable = $feature)]
+        pub(super) fn absorb(deck: &mut $deck, msg: &[u8]) {
+            deck.absorb(msg)
+        }
+
+        #[target_feature(enable = $feature)]
+        pub(super) fn finalize(deck: &mut $deck, ds: u8, ds_bit_width: usize, offset: usize) {
+            deck.finalize(ds, ds_bit_width, offset)
+        }
+
+        #[target_feature(enable = $feature)]
+        pub(super) fn finalize_with_offset(
+            deck: &mut $deck,
+            ds: u8,
+            ds_bit_width: usize,
+            offset: u64,
+        ) {
+            deck.finalize_with_offset(ds, ds_bit_width, offset)
+        }
+
+        #[target_feature(enable = $feature)]
+        pub(super) fn squeeze(deck: &mut $deck, out: &mut [u8]) {
+            deck.squeeze(out)
+        }
+
+        #[target_feature(enable = $feature)]
+        pub(super) fn squeeze_xor(deck: &mut $deck, buf: &mut [u8]) {
+            deck.squeeze_xor(buf)
+        }
+
+        #[target_feature(enable = $feature)]
+        pub(super) fn skip(deck: &mut $deck, n: u64) {
+            deck.skip(n)
+        }
+
+        #[target_feature(enable = $feature)]
+        pub(super) fn squeeze_at(deck: &mut $deck, pos: u64, out: &mut [u8]) {
+            deck.squeeze_at(pos, out)
+        }
+
+        #[target_feature(enable = $feature)]
+        pub(super) fn restart(deck: &mut $deck) {
+            deck.restart()
+        }
+
+        #[target_feature(enable = $feature)]
+        pub(super) fn mac_many(keys: &[&[u8]], msgs: &[&[u8]], outs: &mut [&mut [u8]]) {
+            crate::batch::mac_groups::<$lanes>(keys, msgs, outs)
+        }
+    };
+}
+
+mod avx512 {
+    backend_fns!("avx512f", super::x16::Xoofff, 16);
+}
+
+mod avx2 {
+    backend_fns!("avx2", super::x8::Xoofff, 8);
+}
+
+mod sse2 {
+    backend_fns!("sse2", super::x4::Xoofff, 4);
+}
+
+/// Calls same routine on whichever backend deck function state lives in.
+macro_rules! dispatch {
+    ($self:ident, $fn:ident($($arg:expr),*)) => {
+        match &mut $self.state {
+            // SAFETY: SIMD backend is selected only after detecting, at runtime, that executing
+            // CPU supports target feature, its wrappers are compiled with.
+            State::X16(deck) => unsafe { avx512::$fn(deck, $($arg),*) },
+            State::X8(deck) => unsafe { avx2::$fn(deck, $($arg),*) },
+            State::X4(deck) => unsafe { sse2::$fn(deck, $($arg),*) },
+            State::Serial(deck) => deck.$fn($($arg),*),
+        }
+    };
+}
+
+/// Implementation of Xoofff deck function, which can be explicitly selected, using
+/// `Xoofff::with_backend`, say for benchmarking each of them or for cross-checking them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Backend {
+    /// Serial implementation, permuting one state at a time.
+    Serial,
+    /// 4 -way parallel implementation, requires SSE2.
+    X4,
+    /// 8 -way parallel implementation, requires AVX2.
+    X8,
+    /// 16 -way parallel implementation, requires AVX-512F.
+    X16,
+}
+
+impl Backend {
+    /// All backends, in increasing order of parallelism.
+    pub const ALL: [Backend; 4] = [Backend::Serial, Backend::X4, Backend::X8, Backend::X16];
+
+    /// Returns true if executing CPU supports target feature, required by this backend.
+    #[inline(always)]
+    pub fn is_available(self) -> bool {
+        match self {
+            Backend::Serial => true,
+            Backend::X4 => std::is_x86_feature_detected!("sse2"),
+            Backend::X8 => std::is_x86_feature_detected!("avx2"),
+            Backend::X16 => std::is_x86_feature_detected!("avx512f"),
+        }
+    }
+
+    /// Returns all backends, supported by executing CPU, in increasing order of parallelism.
+    #[inline(always)]
+    pub fn available() -> impl Iterator<Item = Backend> {
+        Self::ALL
+            .into_iter()
+            .filter(|backend| backend.is_available())
+    }
+}
+
+/// Computes MACs over many independent ( key, message ) pairs, see `Xoofff::mac_many`, s.t. each
+/// instance occupies a lane of parallel permutation of selected backend, while serial backend
+/// processes one instance at a time. Panics if executing CPU doesn't support selected backend.
+pub(crate) fn mac_many(keys: &[&[u8]], msgs: &[&[u8]], outs: &mut [&mut [u8]], backend: Backend) {
+    assert!(
+        backend.is_available(),
+        "Executing CPU must support selected backend"
+    );
+
+    match backend {
+        // SAFETY: executing CPU is just detected to support target feature, required by backend
+        Backend::X16 => unsafe { avx512::mac_many(keys, msgs, outs) },
+        Backend::X8 => unsafe { avx2::mac_many(keys, msgs, outs) },
+        Backend::X4 => unsafe { sse2::mac_many(keys, msgs, outs) },
+        Backend::Serial => crate::batch::mac_groups::<1>(keys, msgs, outs),
+    }
+}
+
+/// Deck function state, living in one of the backends. Not boxed, so that no allocation or
+/// extra indirection is needed, at the cost of serial state taking as much space as others.
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone)]
+enum State {
+    X16(x16::Xoofff),
+    X8(x8::Xoofff),
+    X4(x4::Xoofff),
+    Serial(serial::Xoofff),
+}
+
+/// Xoofff deck function, which detects features of executing CPU at runtime, when instantiated,
+/// and dispatches all calls to the best suited backend i.e. 16 -way parallel one when AVX-512F is
+/// available, otherwise 8 -way parallel one when AVX2 is available, otherwise 4 -way parallel one
+/// when SSE2 is available, otherwise serial one. So a
+/// single portable binary runs fast on a mixed fleet, without being compiled for specific CPU.
+/// Any other backend, supported by executing CPU, can be explicitly selected, using `with_backend`.
+///
+/// Note, `is_x86_feature_detected!` caches detected CPU features, so only first instantiation
+/// actually queries CPU. Output of all backends is byte-for-byte same.
+#[derive(Clone)]
+pub struct Xoofff {
+    state: State,
+}
+
+impl Xoofff {
+    /// Create a new instance of Xoofff, with a key of byte length < 48, which
+    /// can be used for incrementally absorbing messages and squeezing output bytes.
+    #[inline(always)]
+    pub fn new(key: &[u8]) -> Self {
+        // most parallel one, among all backends supported by executing CPU
+        let backend = Backend::available().last().unwrap_or(Backend::Serial);
+
+        // SAFETY: backend is just detected to be supported by executing CPU
+        unsafe { Self::with_backend_unchecked(key, backend) }
+    }
+
+    /// Same as `new`, but deck function state lives in explicitly selected backend, instead of
+    /// the best suited one. Returns `None` if executing CPU doesn't support selected backend.
+    #[inline(always)]
+    pub fn with_backend(key: &[u8], backend: Backend) -> Option<Self> {
+        if !backend.is_available() {
+            return None;
+        }
+
+        // SAFETY: backend is just detected to be supported by executing CPU
+        Some(unsafe { Self::with_backend_unchecked(key, backend) })
+    }
+
+    /// Instantiates deck function state in selected backend, without checking its availability.
+    ///
+    /// # Safety
+    ///
+    /// Executing CPU must support target feature, required by selected backend.
+    #[inline(always)]
+    unsafe fn with_backend_unchecked(key: &[u8], backend: Backend) -> Self {
+        let state = match backend {
+            Backend::X16 => State::X16(avx512::new(key)),
+            Backend::X8 => State::X8(avx2::new(key)),
+            Backend::X4 => State::X4(sse2::new(key)),
+            Backend::Serial => State::Serial(serial::Xoofff::new(key)),
+        };
+
+        Self { state }
+    }
+
+    /// Returns the backend, deck function state lives in.
+    #[inline(always)]
+    pub fn backend(&self) -> Backend {
+        match self.state {
+            State::X16(_) => Backend::X16,
+            State::X8(_) => Backend::X8,
+            State::X4(_) => Backend::X4,
+            State::Serial(_) => Backend::Serial,
+        }
+    }
+
+    /// Absorbs message bytes into deck function state, see `absorb` of any backend.
+    #[inline(always)]
+    pub fn absorb(&mut self, msg: &[u8]) {
+        dispatch!(self, absorb(msg))
+    }
+
+    /// Finalizes deck function state, see `finalize` of any backend.
+    #[inline(always)]
+    pub fn finalize(&mut self, domain_seperator: u8, ds_bit_width: usize, offset: usize) {
+        dispatch!(self, finalize(domain_seperator, ds_bit_width, offset))
+    }
+
+    /// Finalizes deck function state, skipping arbitrary many bytes of output, see
+    /// `finalize_with_offset` of any backend.
+    #[inline(always)]
+    pub fn finalize_with_offset(&mut self, domain_seperator: u8, ds_bit_width: usize, offset: u64) {
+        dispatch!(
+            self,
+            finalize_with_offset(domain_seperator, ds_bit_width, offset)
+        )
+    }
+
+    /// Squeezes bytes out of finalized deck function state, see `squeeze` of any backend.
+    #[inline(always)]
+    pub fn squeeze(&mut self, out: &mut [u8]) {
+        dispatch!(self, squeeze(out))
+    }
+
+    /// XORs squeezed bytes into `buf`, see `squeeze_xor` of any backend.
+    #[inline(always)]
+    pub fn squeeze_xor(&mut self, buf: &mut [u8]) {
+        dispatch!(self, squeeze_xor(buf))
+    }
+
+    /// Skips next `n` bytes of output, see `skip` of any backend.
+    #[inline(always)]
+    pub fn skip(&mut self, n: u64) {
+        dispatch!(self, skip(n))
+    }
+
+    /// Fills `out` with output bytes starting at byte position `pos`, see `squeeze_at` of any
+    /// backend.
+    #[inline(always)]
+    pub fn squeeze_at(&mut self, pos: u64, out: &mut [u8]) {
+        dispatch!(self, squeeze_at(pos, out))
+    }
+
+    /// Restarts `absorb->finalize->squeeze` cycle, see `restart` of any backend.
+    #[inline(always)]
+    pub fn restart(&mut self) {
+        dispatch!(self, restart())
+    }
+
+    /// Returns true if deck function state is finalized.
+    #[inline(always)]
+    pub fn is_finalized(&self) -> bool {
+        match &self.state {
+            State::X16(deck) => deck.is_finalized(),
+            State::X8(deck) => deck.is_finalized(),
+            State::X4(deck) => deck.is_finalized(),
+            State::Serial(deck) => deck.is_finalized(),
+        }
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Zeroize for Xoofff {
+    fn zeroize(&mut self) {
+        match &mut self.state {
+            State::X16(deck) => deck.zeroize(),
+            State::X8(deck) => deck.zeroize(),
+            State::X4(deck) => deck.zeroize(),
+            State::Serial(deck) => deck.zeroize(),
+        }
+    }
+}
+
+/// Each backend wipes its own state on drop.
+#[cfg(feature = "zeroize")]
+impl ZeroizeOnDrop for Xoofff {}
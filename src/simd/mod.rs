@@ -1,50 +1,75 @@
-mod xoodoo;
-
 // target_arch || target_feature || x   || runtime detection possible
 // wasm32      || simd128        || x4  || no, make two modules
-// x86/x86_64  || avx2           || x8  || yes
-// x86/x86_64  || avx512         || x16 || yes
-
-#[cfg(feature = "dev")]
-pub use xoodoo::permutex;
+// x86/x86_64  || sse2           || x4  || yes, with `std`
+// x86/x86_64  || avx2           || x8  || yes, with `std`
+// x86/x86_64  || avx512f        || x16 || yes, with `std`
 
-#[cfg(feature = "dev")]
-#[cfg(not(any(target_arch = "x86_64", target_arch = "x86")))]
-mod xoodoo_gene;
-#[cfg(any(target_arch = "x86_64", target_arch = "x86"))]
-mod xoodoo_x86;
-#[cfg(not(feature = "dev"))]
-#[cfg(not(any(target_arch = "x86_64", target_arch = "x86")))]
 mod xoodoo_gene;
-#[cfg(any(target_arch = "x86_64", target_arch = "x86"))]
-mod xoodoo_x86;
 
 #[cfg(all(
     any(target_arch = "x86", target_arch = "x86_64"),
-    target_feature = "avx512"
+    any(feature = "std", target_feature = "avx512f", test)
 ))]
 mod x16;
 
 #[cfg(all(
     any(target_arch = "x86", target_arch = "x86_64"),
-    target_feature = "avx512"
+    any(feature = "std", not(target_feature = "avx512f"), test)
 ))]
-pub use x16::Xoofff;
+mod x8;
+
+#[cfg(all(any(target_arch = "x86", target_arch = "x86_64"), feature = "std"))]
+mod dispatch;
+
+#[cfg(all(any(target_arch = "x86", target_arch = "x86_64"), feature = "std"))]
+pub use dispatch::{Backend, Xoofff};
+
+#[cfg(all(any(target_arch = "x86", target_arch = "x86_64"), feature = "std"))]
+pub(crate) use dispatch::mac_many;
 
 #[cfg(all(
     any(target_arch = "x86", target_arch = "x86_64"),
-    target_feature = "avx2"
+    not(feature = "std"),
+    target_feature = "avx512f"
 ))]
-mod x8;
+pub use x16::Xoofff;
 
 #[cfg(all(
     any(target_arch = "x86", target_arch = "x86_64"),
-    target_feature = "avx2"
+    not(feature = "std"),
+    not(target_feature = "avx512f")
 ))]
 pub use x8::Xoofff;
 
-#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+#[cfg(any(
+    all(target_arch = "wasm32", target_feature = "simd128"),
+    all(any(target_arch = "x86", target_arch = "x86_64"), feature = "std"),
+    test
+))]
 mod x4;
 
 #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
 pub use x4::Xoofff;
+
+/// Applies Xoodoo\[6\] permutation on each of N independent states, by transposing them into
+/// lanes of parallel permutation, so that i -th state occupies i -th lane.
+#[inline(always)]
+pub(crate) fn permute_lanes<const N: usize>(states: &mut [[u32; 12]; N]) {
+    let mut statex = [core::simd::Simd::<u32, N>::splat(0u32); 12];
+
+    for (j, lane) in statex.iter_mut().enumerate() {
+        *lane = core::simd::Simd::from_array(core::array::from_fn(|i| states[i][j]));
+    }
+
+    xoodoo_gene::permutex::<N, 6>(&mut statex);
+
+    for (j, lane) in statex.iter().enumerate() {
+        let arr = lane.to_array();
+        for i in 0..N {
+            states[i][j] = arr[i];
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,227 @@
+use crate::xoofff as serial;
+use rand::{thread_rng, Rng};
+use std::prelude::rust_2021::*;
+use std::vec;
+use test_case::test_case;
+
+/// Random inputs of one differential test iteration, s.t. message is absorbed and output is
+/// squeezed, in arbitrary sized chunks.
+struct Inputs {
+    key: Vec<u8>,
+    msgs: Vec<Vec<u8>>,       // one message per `absorb->finalize->squeeze` cycle
+    splits: Vec<usize>,       // chunk byte lengths, message is absorbed in
+    ds: u8,                   // domain seperator
+    ds_bit_width: usize,      // bit width of domain seperator
+    offset: u64,              // # -of output bytes skipped during finalization
+    outs: Vec<usize>,         // chunk byte lengths, output is squeezed in
+    seeks: Vec<(u64, usize)>, // random-access reads, as ( position, byte length )
+}
+
+impl Inputs {
+    fn random<R: Rng>(rng: &mut R, max_mlen: usize, max_dlen: usize) -> Self {
+        let mut key = vec![0u8; rng.gen_range(0..48)];
+        rng.fill_bytes(&mut key);
+
+        let msgs = (0..rng.gen_range(1..=3))
+            .map(|_| {
+                let mut msg = vec![0u8; rng.gen_range(0..=max_mlen)];
+                rng.fill_bytes(&mut msg);
+                msg
+            })
+            .collect();
+
+        let ds_bit_width = rng.gen_range(0..=7);
+        let offset = match rng.gen_range(0..3) {
+            0 => 0,
+            1 => rng.gen_range(0..=48),
+            _ => rng.gen_range(0..=4 * max_dlen as u64),
+        };
+
+        Self {
+            key,
+            msgs,
+            splits: (0..8).map(|_| rng.gen_range(1..=max_mlen + 1)).collect(),
+            ds: rng.gen(),
+            ds_bit_width,
+            offset,
+            outs: (0..8).map(|_| rng.gen_range(0..=max_dlen)).collect(),
+            seeks: (0..4)
+                .map(|_| {
+                    (
+                        rng.gen_range(0..=4 * max_dlen as u64),
+                        rng.gen_range(0..=max_dlen),
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Runs all `absorb->finalize->squeeze` cycles of given inputs on deck function, instantiated
+/// using `$new`, returning concatenation of everything squeezed, using all of `squeeze`,
+/// `squeeze_xor`, `skip` and `squeeze_at`.
+macro_rules! run {
+    ($new:expr, $inp:expr) => {{
+        let inp: &Inputs = $inp;
+        let mut res = Vec::new();
+
+        let mut deck = $new(&inp.key);
+        for (cycle, msg) in inp.msgs.iter().enumerate() {
+            if cycle > 0 {
+                deck.restart();
+            }
+
+            let mut off = 0;
+            for &split in inp.splits.iter().cycle() {
+                if off >= msg.len() {
+                    break;
+                }
+
+                let end = core::cmp::min(off + split, msg.len());
+                deck.absorb(&msg[off..end]);
+                off = end;
+            }
+
+            if inp.offset <= 48 {
+                deck.finalize(inp.ds, inp.ds_bit_width, inp.offset as usize);
+            } else {
+                deck.finalize_with_offset(inp.ds, inp.ds_bit_width, inp.offset);
+            }
+            assert!(deck.is_finalized());
+
+            for (i, &len) in inp.outs.iter().enumerate() {
+                let mut out = vec![0xa5u8; len];
+                match i % 3 {
+                    0 => deck.squeeze(&mut out),
+                    1 => deck.squeeze_xor(&mut out),
+                    _ => {
+                        deck.skip(len as u64 / 2);
+                        deck.squeeze(&mut out);
+                    }
+                }
+                res.extend_from_slice(&out);
+            }
+
+            for &(pos, len) in inp.seeks.iter() {
+                let mut out = vec![0u8; len];
+                deck.squeeze_at(pos, &mut out);
+                res.extend_from_slice(&out);
+            }
+        }
+
+        res
+    }};
+}
+
+/// Differential test, comparing output of every parallel backend ( and runtime dispatching deck
+/// function, wherever available ) against serial Xoofff, over random keys, message/ output byte
+/// lengths, domain seperators and offsets.
+#[test_case(64, 64; "short messages")]
+#[test_case(2000, 2000; "a few parallel block groups")]
+#[test_case(10000, 40000; "many parallel block groups")]
+fn test_simd_backends_match_serial(max_mlen: usize, max_dlen: usize) {
+    const ITERATIONS: usize = 64;
+
+    let mut rng = thread_rng();
+
+    for _ in 0..ITERATIONS {
+        let inp = Inputs::random(&mut rng, max_mlen, max_dlen);
+        let expected = run!(serial::Xoofff::new, &inp);
+
+        assert_eq!(run!(super::x4::Xoofff::new, &inp), expected, "x4 backend");
+
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        {
+            assert_eq!(run!(super::x8::Xoofff::new, &inp), expected, "x8 backend");
+            assert_eq!(run!(super::x16::Xoofff::new, &inp), expected, "x16 backend");
+        }
+
+        assert_eq!(run!(crate::Xoofff::new, &inp), expected, "default backend");
+
+        // each explicitly selectable backend, supported by executing CPU
+        #[cfg(all(any(target_arch = "x86", target_arch = "x86_64"), feature = "std"))]
+        for backend in super::Backend::available() {
+            let new = |key: &[u8]| super::Xoofff::with_backend(key, backend).unwrap();
+            assert_eq!(run!(new, &inp), expected, "{:?} backend", backend);
+        }
+    }
+}
+
+/// Test that explicitly selected backend is used if and only if executing CPU supports it.
+#[cfg(all(any(target_arch = "x86", target_arch = "x86_64"), feature = "std"))]
+#[test]
+fn test_simd_backend_selection() {
+    use super::{Backend, Xoofff};
+
+    let key = [0xa5u8; 32];
+
+    assert_eq!(Backend::available().next(), Some(Backend::Serial));
+    assert!(Backend::available().any(|backend| Xoofff::new(&key).backend() == backend));
+
+    for backend in Backend::ALL {
+        match Xoofff::with_backend(&key, backend) {
+            Some(deck) => {
+                assert!(backend.is_available());
+                assert_eq!(deck.backend(), backend);
+            }
+            None => assert!(!backend.is_available()),
+        }
+    }
+}
+
+/// Test that computing MACs over many independent ( key, message ) pairs, in lanes of parallel
+/// permutation of each backend, supported by executing CPU, produces same output as serial Xoofff.
+#[cfg(all(any(target_arch = "x86", target_arch = "x86_64"), feature = "std"))]
+#[test_case(1; "instances = 1")]
+#[test_case(17; "instances = 17")]
+#[test_case(100; "instances = 100")]
+fn test_simd_mac_many_backends(cnt: usize) {
+    let mut rng = thread_rng();
+
+    let keys = (0..cnt)
+        .map(|_| {
+            let mut key = vec![0u8; rng.gen_range(0..48)];
+            rng.fill(key.as_mut_slice());
+            key
+        })
+        .collect::<Vec<_>>();
+    let msgs = (0..cnt)
+        .map(|_| {
+            let mut msg = vec![0u8; rng.gen_range(0..500)];
+            rng.fill(msg.as_mut_slice());
+            msg
+        })
+        .collect::<Vec<_>>();
+    let dlens = (0..cnt).map(|_| rng.gen_range(0..120)).collect::<Vec<_>>();
+
+    let expected = keys
+        .iter()
+        .zip(msgs.iter())
+        .zip(dlens.iter())
+        .map(|((key, msg), &dlen)| {
+            let mut out = vec![0u8; dlen];
+            let mut deck = serial::Xoofff::new(key);
+            deck.absorb(msg);
+            deck.finalize(0, 0, 0);
+            deck.squeeze(&mut out);
+            out
+        })
+        .collect::<Vec<_>>();
+
+    let keys = keys.iter().map(|key| key.as_slice()).collect::<Vec<_>>();
+    let msgs = msgs.iter().map(|msg| msg.as_slice()).collect::<Vec<_>>();
+
+    for backend in super::Backend::available() {
+        let mut outs = dlens
+            .iter()
+            .map(|&dlen| vec![0u8; dlen])
+            .collect::<Vec<_>>();
+        let mut outs_ref = outs
+            .iter_mut()
+            .map(|out| out.as_mut_slice())
+            .collect::<Vec<_>>();
+
+        super::mac_many(&keys, &msgs, &mut outs_ref, backend);
+        assert_eq!(outs, expected, "{:?} backend", backend);
+    }
+}
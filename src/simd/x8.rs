@@ -1,14 +1,13 @@
 use crate::rolling;
 use crate::xoodoo as serial_xoodoo;
-use crate::xoofff::{bytes_to_le_words, pad10x, words_to_le_bytes};
-use core::simd::{u32x8, SimdUint};
+use crate::xoofff::{bytes_to_le_words, pad10x, wipe, words_to_le_bytes, xor_bytes, xor_le_words};
+use core::cmp;
+use core::simd::{num::SimdUint, u32x8};
 use crunchy::unroll;
-use std::cmp;
+#[cfg(feature = "zeroize")]
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
-#[cfg(not(any(target_arch = "x86_64", target_arch = "x86")))]
-use crate::xoodoo_gene as xoodoo;
-#[cfg(any(target_arch = "x86_64", target_arch = "x86"))]
-use crate::xoodoo_x86 as xoodoo;
+use super::xoodoo_gene as xoodoo;
 
 /// Xoodoo\[n_r\] being a 384-bit permutation messages are consumed in 48-byte chunks.
 const BLOCK_SIZE: usize = 48;
@@ -20,7 +19,7 @@ const PAR_BLOCK_SIZE: usize = BLOCK_SIZE * 8;
 const ROUNDS: usize = 6;
 
 /// \# -of lanes ( each of 32 -bit width ) in Xoodoo permutation state
-const LANE_CNT: usize = BLOCK_SIZE / std::mem::size_of::<u32>();
+const LANE_CNT: usize = BLOCK_SIZE / core::mem::size_of::<u32>();
 
 /// Xoofff is a deck function, obtained by instantiating Farfalle construction with
 /// Xoodoo\[6\] permutation and two rolling functions, having nice incremental input/
@@ -37,15 +36,20 @@ const LANE_CNT: usize = BLOCK_SIZE / std::mem::size_of::<u32>();
 /// See https://ia.cr/2016/1188 for definition of Farfalle.
 /// Also see https://ia.cr/2018/767 for definition of Xoofff.
 ///
+/// When `zeroize` feature is enabled, whole deck function state, including the parallel
+/// key-stream buffer, is wiped on drop.
 #[derive(Clone)]
 pub struct Xoofff {
     imask: [u32; LANE_CNT],     // input mask
     omask: [u32; LANE_CNT],     // output mask
+    obase: [u32; LANE_CNT],     // output mask of very first output block
     acc: [u32x8; LANE_CNT],     // accumulator
     iblk: [u8; PAR_BLOCK_SIZE], // input message block ( buffer )
     oblk: [u8; PAR_BLOCK_SIZE], // output message block ( buffer )
     ioff: usize,                // offset into input message block
     ooff: usize,                // offset into output message block
+    oidx: u64,                  // index of output block group, currently held in output buffer
+    oskip: u64,                 // # -of output bytes skipped during finalization
     finalized: usize,           // is deck function state finalized ?
 }
 
@@ -60,20 +64,27 @@ impl Xoofff {
             BLOCK_SIZE
         );
 
-        let padded_key = pad10x(key);
+        let mut padded_key = pad10x(key);
         let mut masked_key = bytes_to_le_words(&padded_key);
-        serial_xoodoo::permute::<ROUNDS>(&mut masked_key);
+        wipe(&mut padded_key);
+        serial_xoodoo::permute_fast::<ROUNDS>(&mut masked_key);
 
-        Self {
+        let deck = Self {
             imask: masked_key,
             omask: [0u32; LANE_CNT],
+            obase: [0u32; LANE_CNT],
             acc: [u32x8::splat(0u32); LANE_CNT],
             iblk: [0u8; PAR_BLOCK_SIZE],
             oblk: [0u8; PAR_BLOCK_SIZE],
             ioff: 0,
             ooff: 0,
+            oidx: 0,
+            oskip: 0,
             finalized: usize::MIN,
-        }
+        };
+
+        wipe(&mut masked_key);
+        deck
     }
 
     /// Given a message M of byte length N (>=0), this routine can be used for absorbing
@@ -83,68 +94,42 @@ impl Xoofff {
     /// Note, this function can be called multiple times until Xoofff state is finalized. Once
     /// finalized, bytes can be squeezed out of deck function state. Even after finalization
     /// new absorption->finalization->squeezing phase can be started by calling restart function.
+    ///
+    /// Full groups of 8 message blocks are read straight from `msg`, only partial leading/
+    /// trailing message bytes are staged in internal buffer.
     #[inline(always)]
     pub fn absorb(&mut self, msg: &[u8]) {
         if self.finalized == usize::MAX {
             return;
         }
 
-        let par_blk_cnt = (self.ioff + msg.len()) / PAR_BLOCK_SIZE;
-        let mut moff = 0;
-
-        for _ in 0..par_blk_cnt {
-            let byte_cnt = PAR_BLOCK_SIZE - self.ioff;
-            self.iblk[self.ioff..].copy_from_slice(&msg[moff..(moff + byte_cnt)]);
-
-            let mut imasks = [[0u32; 12]; 8];
-            unroll! {
-                for i in 0..8 {
-                    imasks[i] = self.imask;
-                    rolling::roll_xc(&mut self.imask);
-                }
-            }
+        let mut msg = msg;
 
-            let imaskx = words_to_statex8(&imasks);
-
-            let mut words = [[0u32; 12]; 8];
-            unroll! {
-                for i in 0..8 {
-                    words[i] = bytes_to_le_words(
-                        &self.iblk[i * BLOCK_SIZE..(i + 1) * BLOCK_SIZE]
-                            .try_into()
-                            .unwrap(),
-                    );
-                }
-            }
-
-            let mut states = words_to_statex8(&words);
-
-            debug_assert_eq!(LANE_CNT, 12);
-
-            unroll! {
-                for i in 0..12 {
-                    states[i] ^= imaskx[i];
-                }
-            }
+        // complete partially filled group of message blocks, staged in buffer, if any
+        if self.ioff > 0 {
+            let byte_cnt = cmp::min(PAR_BLOCK_SIZE - self.ioff, msg.len());
 
-            xoodoo::permutex::<8, ROUNDS>(&mut states);
+            self.iblk[self.ioff..(self.ioff + byte_cnt)].copy_from_slice(&msg[..byte_cnt]);
+            self.ioff += byte_cnt;
+            msg = &msg[byte_cnt..];
 
-            unroll! {
-                for i in 0..12 {
-                    self.acc[i] ^= states[i];
-                }
+            if self.ioff < PAR_BLOCK_SIZE {
+                return;
             }
 
-            moff += byte_cnt;
+            compress_par_block(&mut self.imask, &mut self.acc, &self.iblk);
             self.ioff = 0;
         }
 
-        let rm_bytes = msg.len() - moff;
-        let dst_frm = self.ioff;
-        let dst_to = dst_frm + rm_bytes;
+        // full groups of message blocks are read straight from input slice, without any staging
+        let mut blks = msg.chunks_exact(PAR_BLOCK_SIZE);
+        for blk in blks.by_ref() {
+            compress_par_block(&mut self.imask, &mut self.acc, blk.try_into().unwrap());
+        }
 
-        self.iblk[dst_frm..dst_to].copy_from_slice(&msg[moff..]);
-        self.ioff += rm_bytes;
+        let rm = blks.remainder();
+        self.iblk[..rm.len()].copy_from_slice(rm);
+        self.ioff = rm.len();
     }
 
     /// Given that arbitrary many message bytes are already absorbed into deck function
@@ -155,7 +140,7 @@ impl Xoofff {
     /// - Attempting to absorb new message bytes on already finalized state, does nothing.
     /// - After finalization, one might start squeezing arbitrary many output bytes.
     /// - After finishing squeezing, when new message arrives, arbitrary many bytes
-    /// can be consumed into deck function state, by restarting `absorb->finalize->squeeze` cycle.
+    ///   can be consumed into deck function state, by restarting `absorb->finalize->squeeze` cycle.
     ///
     /// This routine implements portion of algorithm 1 of https://ia.cr/2016/1188.
     #[inline(always)]
@@ -164,6 +149,15 @@ impl Xoofff {
             offset <= BLOCK_SIZE,
             "Byte offset, considered during squeezing, must be <= 48 -bytes"
         );
+
+        self.finalize_with_offset(domain_seperator, ds_bit_width, offset as u64);
+    }
+
+    /// Same as `finalize`, but allows skipping arbitrary many ( i.e. not limited to <= 48 )
+    /// bytes of output, before squeezing begins. Skipped output blocks are never computed,
+    /// only output mask is rolled forward, which costs a tiny fraction of a permutation.
+    #[inline(always)]
+    pub fn finalize_with_offset(&mut self, domain_seperator: u8, ds_bit_width: usize, offset: u64) {
         debug_assert!(
             ds_bit_width <= 7,
             "Domain seperator bit width is not allowed to be > 7"
@@ -198,7 +192,7 @@ impl Xoofff {
                 }
             }
 
-            serial_xoodoo::permute::<ROUNDS>(&mut words);
+            serial_xoodoo::permute_fast::<ROUNDS>(&mut words);
 
             unroll! {
                 for j in 0..12 {
@@ -219,7 +213,7 @@ impl Xoofff {
 
         rolling::roll_xc(&mut self.imask);
 
-        self.iblk.fill(0);
+        wipe(&mut self.iblk);
         self.ioff = 0;
         self.finalized = usize::MAX;
 
@@ -229,30 +223,14 @@ impl Xoofff {
             }
         }
 
-        serial_xoodoo::permute::<ROUNDS>(&mut self.omask);
+        serial_xoodoo::permute_fast::<ROUNDS>(&mut self.omask);
 
-        let mut omasks = [[0u32; 12]; 8];
-        unroll! {
-            for i in 0..8 {
-                omasks[i] = self.omask;
-                rolling::roll_xe(&mut self.omask);
-            }
-        }
+        self.obase = self.omask;
+        self.oidx = 0;
+        self.oskip = offset;
 
-        let mut states = words_to_statex8(&omasks);
-
-        xoodoo::permutex::<8, ROUNDS>(&mut states);
-
-        debug_assert_eq!(LANE_CNT, 12);
-        unroll! {
-            for i in 0..12 {
-                states[i] ^= u32x8::splat(self.imask[i]);
-            }
-        }
-
-        statex8_to_bytes(&states, &mut self.oblk);
-
-        self.ooff = offset;
+        self.expand();
+        self.seek_to(offset);
     }
 
     /// Given that N -many message bytes are already absorbed into deck function state and
@@ -278,31 +256,139 @@ impl Xoofff {
             off += read;
 
             if self.ooff == PAR_BLOCK_SIZE {
-                let mut omasks = [[0u32; 12]; 8];
+                self.expand();
+                self.oidx += 1;
+                self.ooff = 0;
+            }
+        }
+    }
 
-                unroll! {
-                    for i in 0..8 {
-                        omasks[i] = self.omask;
-                        rolling::roll_xe(&mut self.omask);
-                    }
-                }
+    /// Same as `squeeze`, but instead of overwriting `buf`, squeezed bytes are XORed into it,
+    /// which is what a stream cipher does for encrypting/ decrypting in-place. Full groups of
+    /// 8 output blocks are XORed straight into `buf`, without going through output buffer.
+    #[inline(always)]
+    pub fn squeeze_xor(&mut self, buf: &mut [u8]) {
+        if self.finalized != usize::MAX {
+            return;
+        }
 
-                let mut states = words_to_statex8(&omasks);
+        // consume ( rest of ) output block group, which is already buffered
+        let read = cmp::min(PAR_BLOCK_SIZE - self.ooff, buf.len());
+        let (head, buf) = buf.split_at_mut(read);
+        xor_bytes(head, &self.oblk[self.ooff..self.ooff + read]);
 
-                xoodoo::permutex::<8, ROUNDS>(&mut states);
+        self.ooff += read;
+        if self.ooff < PAR_BLOCK_SIZE {
+            return;
+        }
 
-                debug_assert_eq!(LANE_CNT, 12);
-                unroll! {
-                    for i in 0..12 {
-                        states[i] ^= u32x8::splat(self.imask[i]);
-                    }
-                }
+        let (blks, rm) = buf.split_at_mut(buf.len() - buf.len() % PAR_BLOCK_SIZE);
+        for grp in blks.chunks_exact_mut(PAR_BLOCK_SIZE) {
+            let words = statex8_to_words(&self.expand_states());
 
-                statex8_to_bytes(&states, &mut self.oblk);
+            for (blk, words) in grp.chunks_exact_mut(BLOCK_SIZE).zip(words.iter()) {
+                xor_le_words(words, blk.try_into().unwrap());
+            }
+        }
 
-                self.ooff = 0;
+        self.expand();
+        self.oidx += (blks.len() / PAR_BLOCK_SIZE) as u64 + 1;
+
+        xor_bytes(rm, &self.oblk[..rm.len()]);
+        self.ooff = rm.len();
+    }
+
+    /// Given that deck function state is finalized, this routine skips next `n` bytes of
+    /// output, as if those were squeezed and thrown away. Skipped output blocks are never
+    /// computed, only output mask is rolled forward, which costs O(n / 48) roll_Xe invocations.
+    /// Panics if output position overflows `u64`.
+    #[inline(always)]
+    pub fn skip(&mut self, n: u64) {
+        if self.finalized != usize::MAX {
+            return;
+        }
+
+        let pos = self.oidx * PAR_BLOCK_SIZE as u64 + self.ooff as u64;
+        let pos = pos.checked_add(n);
+        self.seek_to(pos.expect("Output position must not overflow u64"));
+    }
+
+    /// Given that deck function state is finalized, this routine fills `out` with output bytes
+    /// starting at byte position `pos` ( i.e. same bytes one would get after squeezing `pos`
+    /// -many bytes from freshly finalized state ), allowing random-access reads of output.
+    /// Next call to `squeeze` continues from where this one ends. Seeking costs same as `skip`,
+    /// when moving ahead, otherwise same as skipping `pos` bytes from freshly finalized state.
+    /// Panics if output position, including skipped offset, overflows `u64`.
+    #[inline(always)]
+    pub fn squeeze_at(&mut self, pos: u64, out: &mut [u8]) {
+        if self.finalized != usize::MAX {
+            return;
+        }
+
+        let pos = self.oskip.checked_add(pos);
+        self.seek_to(pos.expect("Output position must not overflow u64"));
+        self.squeeze(out);
+    }
+
+    /// Moves output cursor to absolute byte position `pos` ( counted from very beginning of
+    /// output, including skipped offset ), computing only the 8 output blocks holding that byte.
+    #[inline(always)]
+    fn seek_to(&mut self, pos: u64) {
+        let grp_idx = pos / PAR_BLOCK_SIZE as u64;
+
+        if grp_idx != self.oidx {
+            // `omask` is the mask for first output block of group `oidx + 1`, so it can be
+            // rolled forward when moving ahead, otherwise start rolling from the very first one
+            let rolls = if grp_idx > self.oidx {
+                (grp_idx - self.oidx - 1) * 8
+            } else {
+                self.omask = self.obase;
+                grp_idx * 8
+            };
+
+            for _ in 0..rolls {
+                rolling::roll_xe(&mut self.omask);
             }
+
+            self.expand();
+            self.oidx = grp_idx;
         }
+
+        self.ooff = (pos % PAR_BLOCK_SIZE as u64) as usize;
+    }
+
+    /// Computes next 8 output blocks in parallel, from current output mask, which is
+    /// then rolled forward 8 times.
+    #[inline(always)]
+    fn expand(&mut self) {
+        let states = self.expand_states();
+        statex8_to_bytes(&states, &mut self.oblk);
+    }
+
+    /// Computes next group of 8 output blocks, from current output mask, which is then rolled
+    /// forward.
+    #[inline(always)]
+    fn expand_states(&mut self) -> [u32x8; LANE_CNT] {
+        let mut omasks = [[0u32; 12]; 8];
+        unroll! {
+            for i in 0..8 {
+                omasks[i] = self.omask;
+                rolling::roll_xe(&mut self.omask);
+            }
+        }
+
+        let mut states = words_to_statex8(&omasks);
+
+        xoodoo::permutex::<8, ROUNDS>(&mut states);
+
+        debug_assert_eq!(LANE_CNT, 12);
+        unroll! {
+            for i in 0..12 {
+                states[i] ^= u32x8::splat(self.imask[i]);
+            }
+        }
+
+        states
     }
 
     /// Given that a message of arbitrary byte length is absorbed into deck function state and
@@ -321,13 +407,52 @@ impl Xoofff {
             return;
         }
 
-        self.omask.fill(0);
-        self.oblk.fill(0);
+        wipe(&mut self.omask);
+        wipe(&mut self.obase);
+        wipe(&mut self.oblk);
         self.ooff = 0;
+        self.oidx = 0;
+        self.oskip = 0;
         self.finalized = usize::MIN;
     }
+
+    /// Returns true if deck function state is finalized i.e. it's ready to be squeezed, but
+    /// can't absorb any more message bytes, until `absorb->finalize->squeeze` cycle is restarted.
+    #[inline(always)]
+    pub fn is_finalized(&self) -> bool {
+        self.finalized == usize::MAX
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Zeroize for Xoofff {
+    fn zeroize(&mut self) {
+        self.imask.zeroize();
+        self.omask.zeroize();
+        self.obase.zeroize();
+        for lane in self.acc.iter_mut() {
+            lane.as_mut_array().zeroize();
+        }
+        self.iblk.zeroize();
+        self.oblk.zeroize();
+        self.ioff.zeroize();
+        self.ooff.zeroize();
+        self.oidx.zeroize();
+        self.oskip.zeroize();
+        self.finalized.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for Xoofff {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
 }
 
+#[cfg(feature = "zeroize")]
+impl ZeroizeOnDrop for Xoofff {}
+
 #[inline(always)]
 pub fn statex8_to_words(states: &[u32x8; LANE_CNT]) -> [[u32; LANE_CNT]; 8] {
     let mut words = [[0u32; LANE_CNT]; 8];
@@ -348,7 +473,7 @@ pub fn statex8_to_words(states: &[u32x8; LANE_CNT]) -> [[u32; LANE_CNT]; 8] {
 
 #[inline(always)]
 pub fn statex8_to_bytes(states: &[u32x8; LANE_CNT], out: &mut [u8; PAR_BLOCK_SIZE]) {
-    let words = statex8_to_words(&states);
+    let words = statex8_to_words(states);
 
     unroll! {
         for i in 0..8 {
@@ -362,6 +487,54 @@ pub fn statex8_to_bytes(states: &[u32x8; LANE_CNT], out: &mut [u8; PAR_BLOCK_SIZ
     }
 }
 
+/// Compresses a group of 8 consecutive message blocks into accumulator `acc`, in parallel, s.t.
+/// first block is masked using `imask`, which is rolled once after each block.
+#[inline(always)]
+fn compress_par_block(
+    imask: &mut [u32; LANE_CNT],
+    acc: &mut [u32x8; LANE_CNT],
+    blks: &[u8; PAR_BLOCK_SIZE],
+) {
+    let mut imasks = [[0u32; 12]; 8];
+    unroll! {
+        for i in 0..8 {
+            imasks[i] = *imask;
+            rolling::roll_xc(imask);
+        }
+    }
+
+    let imaskx = words_to_statex8(&imasks);
+
+    let mut words = [[0u32; 12]; 8];
+    unroll! {
+        for i in 0..8 {
+            words[i] = bytes_to_le_words(
+                &blks[i * BLOCK_SIZE..(i + 1) * BLOCK_SIZE]
+                    .try_into()
+                    .unwrap(),
+            );
+        }
+    }
+
+    let mut states = words_to_statex8(&words);
+
+    debug_assert_eq!(LANE_CNT, 12);
+
+    unroll! {
+        for i in 0..12 {
+            states[i] ^= imaskx[i];
+        }
+    }
+
+    xoodoo::permutex::<8, ROUNDS>(&mut states);
+
+    unroll! {
+        for i in 0..12 {
+            acc[i] ^= states[i];
+        }
+    }
+}
+
 #[inline(always)]
 fn words_to_statex8(words: &[[u32; LANE_CNT]; 8]) -> [u32x8; LANE_CNT] {
     let mut states = [u32x8::splat(0u32); LANE_CNT];
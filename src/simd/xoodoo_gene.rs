@@ -1,4 +1,4 @@
-use core::simd::{LaneCount, Simd, SupportedLaneCount};
+use core::simd::Simd;
 use crunchy::unroll;
 
 /// Maximum number of rounds one can request to have when applying Xoodoo\[n_r\] permutation i.e. n_r <= MAX_ROUNDS
@@ -15,10 +15,7 @@ const RC: [u32; MAX_ROUNDS] = [
 #[inline(always)]
 pub fn cyclic_shiftx<const N: usize, const T: usize, const V: u32>(
     plane: &[Simd<u32, N>],
-) -> [Simd<u32, N>; 4]
-where
-    LaneCount<N>: SupportedLaneCount,
-{
+) -> [Simd<u32, N>; 4] {
     debug_assert!(
         plane.len() == 4,
         "Each lane of Xoodoo permutation state must have four lanes !"
@@ -39,10 +36,7 @@ where
 
 /// θ step mapping of Xoodoo permutation, as described in algorithm 1 of https://ia.cr/2018/767.
 #[inline(always)]
-fn thetax<const N: usize>(state: &mut [Simd<u32, N>])
-where
-    LaneCount<N>: SupportedLaneCount,
-{
+fn thetax<const N: usize>(state: &mut [Simd<u32, N>]) {
     debug_assert!(
         state.len() == 12,
         "Xoodoo permutation state must have 12 lanes !"
@@ -51,7 +45,7 @@ where
     let mut p = [Simd::<u32, N>::splat(0u32); 4];
     unroll! {
         for i in (0..12).step_by(4) {
-            p[0] ^= state[i + 0];
+            p[0] ^= state[i];
             p[1] ^= state[i + 1];
             p[2] ^= state[i + 2];
             p[3] ^= state[i + 3];
@@ -70,7 +64,7 @@ where
 
     unroll! {
         for i in (0..12).step_by(4) {
-            state[i + 0] ^= e[0];
+            state[i] ^= e[0];
             state[i + 1] ^= e[1];
             state[i + 2] ^= e[2];
             state[i + 3] ^= e[3];
@@ -80,10 +74,7 @@ where
 
 /// ρ_west step mapping function of Xoodoo permutation, as described in algorithm 1 of https://ia.cr/2018/767.
 #[inline(always)]
-fn rho_westx<const N: usize>(state: &mut [Simd<u32, N>])
-where
-    LaneCount<N>: SupportedLaneCount,
-{
+fn rho_westx<const N: usize>(state: &mut [Simd<u32, N>]) {
     debug_assert!(
         state.len() == 12,
         "Xoodoo permutation state must have 12 lanes !"
@@ -98,10 +89,7 @@ where
 
 /// ρ_east step mapping function of Xoodoo permutation, as described in algorithm 1 of https://ia.cr/2018/767.
 #[inline(always)]
-fn rho_eastx<const N: usize>(state: &mut [Simd<u32, N>])
-where
-    LaneCount<N>: SupportedLaneCount,
-{
+fn rho_eastx<const N: usize>(state: &mut [Simd<u32, N>]) {
     debug_assert!(
         state.len() == 12,
         "Xoodoo permutation state must have 12 lanes !"
@@ -116,10 +104,7 @@ where
 
 /// ι step mapping function of Xoodoo permutation, as described in algorithm 1 of https://ia.cr/2018/767.
 #[inline(always)]
-fn iotax<const N: usize>(state: &mut [Simd<u32, N>], ridx: usize)
-where
-    LaneCount<N>: SupportedLaneCount,
-{
+fn iotax<const N: usize>(state: &mut [Simd<u32, N>], ridx: usize) {
     debug_assert!(
         state.len() == 12,
         "Xoodoo permutation state must have 12 lanes !"
@@ -130,10 +115,7 @@ where
 
 /// χ step mapping function of Xoodoo permutation, as described in algorithm 1 of https://ia.cr/2018/767.
 #[inline(always)]
-fn chix<const N: usize>(state: &mut [Simd<u32, N>])
-where
-    LaneCount<N>: SupportedLaneCount,
-{
+fn chix<const N: usize>(state: &mut [Simd<u32, N>]) {
     debug_assert!(
         state.len() == 12,
         "Xoodoo permutation state must have 12 lanes !"
@@ -171,10 +153,7 @@ where
 
 /// Round function of Xoodoo permutation, as described in algorithm 1 of https://ia.cr/2018/767.
 #[inline(always)]
-fn roundx<const N: usize>(state: &mut [Simd<u32, N>], ridx: usize)
-where
-    LaneCount<N>: SupportedLaneCount,
-{
+fn roundx<const N: usize>(state: &mut [Simd<u32, N>], ridx: usize) {
     debug_assert!(
         state.len() == 12,
         "Xoodoo permutation state must have 12 lanes !"
@@ -191,18 +170,12 @@ where
 /// Xoodoo\[n_r\] permutation function s.t. n_r ( <= MAX_ROUNDS ) times round function
 /// is applied on permutation state, as described in algorithm 1 of https://ia.cr/2018/767.
 #[inline(always)]
-pub fn permutex<const N: usize, const ROUNDS: usize>(state: &mut [Simd<u32, N>])
-where
-    LaneCount<N>: SupportedLaneCount,
-{
+pub fn permutex<const N: usize, const ROUNDS: usize>(state: &mut [Simd<u32, N>]) {
     debug_assert!(
         state.len() == 12,
         "Xoodoo permutation state must have 12 lanes !"
     );
-    debug_assert!(
-        ROUNDS <= MAX_ROUNDS,
-        "Requested rounds must be < MAX_ROUNDS !"
-    );
+    const { assert!(ROUNDS <= MAX_ROUNDS, "Xoodoo rounds must be <= 12") };
 
     let start = MAX_ROUNDS - ROUNDS;
     for ridx in start..MAX_ROUNDS {
@@ -214,7 +187,7 @@ where
 mod tests {
     #[test]
     fn test_xoodoo_simd() {
-        use crate::simd::xoodoo::permutex;
+        use super::permutex;
         use crate::xoodoo::permute;
         use core::simd::u32x2;
         use rand::{thread_rng, Rng};
@@ -247,4 +220,4 @@ mod tests {
         assert_eq!(state1, state12);
         assert_eq!(state2, state22);
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,81 @@
+use core::fmt;
+
+/// Four magic bytes, prepended to each exported Xoofff state snapshot.
+pub(crate) const MAGIC: [u8; 4] = *b"XFFF";
+
+/// Version of binary snapshot format, bumped whenever layout of exported state changes.
+///
+/// 1: input/ output masks, accumulator and both message blocks. 2: output mask of very first
+/// output block, output block index and # -of skipped output bytes added. 3: input mask of very
+/// first input block added.
+pub(crate) const VERSION: u8 = 3;
+
+/// Bit set in flags byte of the header, when snapshot is followed by an authentication tag.
+pub(crate) const FLAG_AUTHENTICATED: u8 = 0b1;
+
+/// Byte length of snapshot header i.e. magic || version || flags || phase || ioff || ooff ||
+/// oidx || oskip s.t. last two are 64 -bit little endian integers.
+pub(crate) const HEADER_LEN: usize = MAGIC.len() + 5 + 2 * 8;
+
+/// Byte length of serialized Xoofff state s.t. four masks, accumulator and both message blocks
+/// are each of 48 -bytes, following the header.
+pub const STATE_LEN: usize = HEADER_LEN + 7 * 48;
+
+/// Byte length of authentication tag, appended to authenticated state snapshots.
+pub const TAG_LEN: usize = 32;
+
+/// Domain seperator ( and its bit width ) used when finalizing the deck function, which is
+/// keyed with user supplied authentication key, for computing tag over exported state.
+pub(crate) const TAG_DOMAIN_SEPERATOR: u8 = 0b10;
+pub(crate) const TAG_DS_BIT_WIDTH: usize = 2;
+
+/// Errors which can be encountered when importing a previously exported Xoofff state.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StateError {
+    /// Snapshot byte length doesn't match expected length.
+    InvalidLength,
+    /// Snapshot doesn't start with expected magic bytes.
+    InvalidMagic,
+    /// Snapshot format version is not supported by this version of the library.
+    UnsupportedVersion(u8),
+    /// Snapshot carries invalid phase, offset or flags.
+    Malformed,
+    /// Snapshot is ( or is not ) authenticated, while the opposite was expected.
+    UnexpectedAuthentication,
+    /// Authentication tag doesn't match the snapshot i.e. it was tampered with.
+    AuthenticationFailed,
+}
+
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidLength => write!(f, "invalid Xoofff state snapshot length"),
+            Self::InvalidMagic => write!(f, "invalid Xoofff state snapshot magic bytes"),
+            Self::UnsupportedVersion(v) => {
+                write!(f, "unsupported Xoofff state snapshot version {}", v)
+            }
+            Self::Malformed => write!(f, "malformed Xoofff state snapshot"),
+            Self::UnexpectedAuthentication => {
+                write!(f, "unexpected Xoofff state snapshot authentication mode")
+            }
+            Self::AuthenticationFailed => {
+                write!(f, "Xoofff state snapshot authentication failed")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for StateError {}
+
+/// Compares two equal length byte slices, in time independent of their content.
+#[inline(always)]
+pub(crate) fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    debug_assert_eq!(a.len(), b.len());
+
+    let mut diff = 0u8;
+    for (x, y) in a.iter().zip(b.iter()) {
+        diff |= x ^ y;
+    }
+    core::hint::black_box(diff) == 0
+}
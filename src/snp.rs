@@ -0,0 +1,175 @@
+//! Xoodoo permutation state, with byte oriented operations, following "SnP" ( i.e. State and
+//! Permutation ) interface of XKCP, see https://github.com/XKCP/XKCP.
+
+use crate::xoodoo;
+#[cfg(feature = "zeroize")]
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// Xoodoo\[n_r\] being a 384 -bit permutation, state is 48 -bytes wide
+const WIDTH: usize = 48;
+
+/// \# -of lanes ( each of 32 -bit width ) in Xoodoo permutation state
+const LANE_CNT: usize = WIDTH / core::mem::size_of::<u32>();
+
+/// Xoodoo permutation state, which can be accessed as a 48 -bytes array, s.t. i -th byte is
+/// (i % 4) -th byte of (i / 4) -th lane, in little-endian order, so that sponge, duplex or
+/// other permutation based constructions can be written on top of it, without caring about
+/// how bytes are packed into lanes. Operations follow "SnP" interface of XKCP, where byte
+/// offsets are counted from the beginning of the state.
+///
+/// With `zeroize` feature, permutation state is wiped on drop and the type isn't `Copy`.
+#[cfg_attr(not(feature = "zeroize"), derive(Clone, Copy))]
+#[cfg_attr(feature = "zeroize", derive(Clone))]
+pub struct XoodooState {
+    pub(crate) lanes: [u32; LANE_CNT],
+}
+
+impl XoodooState {
+    /// Width of Xoodoo permutation state, in bytes.
+    pub const WIDTH: usize = WIDTH;
+
+    /// Creates a new, all zero, Xoodoo permutation state.
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self {
+            lanes: [0u32; LANE_CNT],
+        }
+    }
+
+    /// Resets permutation state to all zero.
+    #[inline(always)]
+    pub fn initialize(&mut self) {
+        self.lanes = [0u32; LANE_CNT];
+    }
+
+    /// XORs a byte into permutation state, at byte offset `offset` ( < 48 ).
+    #[inline(always)]
+    pub fn add_byte(&mut self, byte: u8, offset: usize) {
+        debug_assert!(offset < WIDTH, "Byte offset must be < 48");
+
+        self.lanes[offset / 4] ^= (byte as u32) << (8 * (offset % 4));
+    }
+
+    /// XORs `data` into permutation state, starting at byte offset `offset`, s.t.
+    /// `offset + data.len() <= 48`.
+    #[inline(always)]
+    pub fn add_bytes(&mut self, offset: usize, data: &[u8]) {
+        debug_assert!(
+            offset + data.len() <= WIDTH,
+            "Bytes must fit in 48 -bytes permutation state"
+        );
+
+        for (i, &byte) in data.iter().enumerate() {
+            self.add_byte(byte, offset + i);
+        }
+    }
+
+    /// Overwrites permutation state with `data`, starting at byte offset `offset`, s.t.
+    /// `offset + data.len() <= 48`.
+    #[inline(always)]
+    pub fn overwrite_bytes(&mut self, offset: usize, data: &[u8]) {
+        debug_assert!(
+            offset + data.len() <= WIDTH,
+            "Bytes must fit in 48 -bytes permutation state"
+        );
+
+        for (i, &byte) in data.iter().enumerate() {
+            let off = offset + i;
+            let shift = 8 * (off % 4);
+
+            let lane = &mut self.lanes[off / 4];
+            *lane = (*lane & !(0xffu32 << shift)) | ((byte as u32) << shift);
+        }
+    }
+
+    /// Overwrites first `byte_cnt` ( <= 48 ) bytes of permutation state with zeroes.
+    #[inline(always)]
+    pub fn overwrite_with_zeroes(&mut self, byte_cnt: usize) {
+        debug_assert!(byte_cnt <= WIDTH, "Byte count must be <= 48");
+
+        let full = byte_cnt / 4;
+        self.lanes[..full].fill(0);
+
+        let rm = byte_cnt % 4;
+        if rm > 0 {
+            self.lanes[full] &= u32::MAX << (8 * rm);
+        }
+    }
+
+    /// Applies Xoodoo\[n_r\] permutation on state s.t. `rounds` ( <= 12 ) is n_r. Panics if
+    /// `rounds` > 12.
+    #[inline(always)]
+    pub fn permute_nrounds(&mut self, rounds: usize) {
+        assert!(
+            rounds <= xoodoo::MAX_ROUNDS,
+            "Requested rounds must be <= 12"
+        );
+
+        match rounds {
+            6 => xoodoo::permute_fast::<6>(&mut self.lanes),
+            12 => xoodoo::permute_fast::<12>(&mut self.lanes),
+            _ => xoodoo::permute_nrounds(&mut self.lanes, rounds),
+        }
+    }
+
+    /// Copies bytes of permutation state into `out`, starting at byte offset `offset`, s.t.
+    /// `offset + out.len() <= 48`.
+    #[inline(always)]
+    pub fn extract_bytes(&self, offset: usize, out: &mut [u8]) {
+        debug_assert!(
+            offset + out.len() <= WIDTH,
+            "Bytes must fit in 48 -bytes permutation state"
+        );
+
+        for (i, byte) in out.iter_mut().enumerate() {
+            *byte = self.byte(offset + i);
+        }
+    }
+
+    /// XORs bytes of permutation state, starting at byte offset `offset`, with `input` and writes
+    /// result into `output`, s.t. both of them are of same length and `offset + output.len() <= 48`.
+    #[inline(always)]
+    pub fn extract_and_add_bytes(&self, offset: usize, input: &[u8], output: &mut [u8]) {
+        debug_assert!(
+            input.len() == output.len(),
+            "Input and output must be of same byte length"
+        );
+        debug_assert!(
+            offset + output.len() <= WIDTH,
+            "Bytes must fit in 48 -bytes permutation state"
+        );
+
+        for (i, (out, &inp)) in output.iter_mut().zip(input.iter()).enumerate() {
+            *out = inp ^ self.byte(offset + i);
+        }
+    }
+
+    /// Returns byte of permutation state, at byte offset `offset`.
+    #[inline(always)]
+    fn byte(&self, offset: usize) -> u8 {
+        (self.lanes[offset / 4] >> (8 * (offset % 4))) as u8
+    }
+}
+
+impl Default for XoodooState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Zeroize for XoodooState {
+    fn zeroize(&mut self) {
+        self.lanes.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for XoodooState {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl ZeroizeOnDrop for XoodooState {}
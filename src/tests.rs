@@ -3,39 +3,79 @@ use rand::{thread_rng, RngCore};
 use std::cmp;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
+use std::prelude::rust_2021::*;
+use std::vec;
 use test_case::test_case;
 
+/// Reads known answer tests from `kat_file`, s.t. each of them is `n` -many lines, each of form
+/// `<name> <value>`, followed by an empty line, returning values of each of them, in order.
+fn read_kats(kat_file: &str, n: usize) -> Vec<Vec<String>> {
+    let file = File::open(kat_file).expect(kat_file);
+    let lines = BufReader::new(file)
+        .lines()
+        .map(|line| line.unwrap())
+        .collect::<Vec<_>>();
+
+    lines
+        .chunks(n + 1)
+        .map(|kat| {
+            kat[..n]
+                .iter()
+                .map(|line| line.split(" ").collect::<Vec<_>>()[1].to_string())
+                .collect()
+        })
+        .collect()
+}
+
+/// Returns `len` -many random bytes.
+fn random_bytes(len: usize) -> Vec<u8> {
+    let mut bytes = vec![0u8; len];
+    thread_rng().fill_bytes(&mut bytes);
+    bytes
+}
+
+/// Returns random Xoodoo permutation state.
+fn random_state() -> [u32; 12] {
+    let mut rng = thread_rng();
+    core::array::from_fn(|_| rng.next_u32())
+}
+
+/// Computes `dlen` -bytes output of Xoofff, keyed with `key`, over `msg`, finalized using domain
+/// seperator `ds` of bit width `ds_bits` and skipping `offset` -bytes, all in one go, s.t. other
+/// ways of driving Xoofff can be checked against it, while it's checked against known answers.
+fn xoofff_oneshot(
+    key: &[u8],
+    msg: &[u8],
+    ds: u8,
+    ds_bits: usize,
+    offset: u64,
+    dlen: usize,
+) -> Vec<u8> {
+    let mut out = vec![0u8; dlen];
+
+    let mut deck = Xoofff::new(key);
+    deck.absorb(msg);
+    deck.finalize_with_offset(ds, ds_bits, offset);
+    deck.squeeze(&mut out);
+
+    out
+}
+
 /// Test functional correctness of Xoofff deck function, by using
 /// known answer tests, generated by following steps described on
 /// https://gist.github.com/itzmeanjan/504113021dec30a0909e5f5b47a5bde5
 #[test]
 fn test_xoofff_kat() {
-    let kat_file = "./kats/Xoofff_KAT.txt";
-    let file = File::open(kat_file).unwrap();
-    let mut reader = BufReader::new(file).lines();
-
-    while let Some(line) = reader.next() {
+    for kat in read_kats("./kats/Xoofff_KAT.txt", 4) {
         // key to be used for instantiating deck function
-        let key = line.unwrap();
-        let key = key.split(" ").collect::<Vec<_>>()[1];
-        let key = hex::decode(key).unwrap();
-
+        let key = hex::decode(&kat[0]).unwrap();
         // message to be absorbed into deck function
-        let msg = reader.next().unwrap().unwrap();
-        let msg = msg.split(" ").collect::<Vec<_>>()[1];
-        let msg = hex::decode(msg).unwrap();
-
+        let msg = hex::decode(&kat[1]).unwrap();
         // # -of bytes to be skipped before message squeezing begins
-        let q = reader.next().unwrap().unwrap();
-        let q = q.split(" ").collect::<Vec<_>>()[1]
-            .parse::<usize>()
-            .unwrap();
-
-        let out = reader.next().unwrap().unwrap();
-        let out = out.split(" ").collect::<Vec<_>>()[1];
+        let q = kat[2].parse::<usize>().unwrap();
 
         // expected squeezed bytes
-        let expected = hex::decode(out).unwrap();
+        let expected = hex::decode(&kat[3]).unwrap();
         // to be squeezed bytes
         let mut computed = vec![0u8; expected.len()];
 
@@ -53,7 +93,13 @@ fn test_xoofff_kat() {
             q
         );
 
-        reader.next().unwrap().unwrap(); // skip the empty line
+        // compact Xoofff must produce same answers
+        let mut deck = crate::compact::Xoofff::new(&key);
+        deck.absorb(&msg);
+        deck.finalize(0, 0, q);
+        deck.squeeze(&mut computed);
+
+        assert_eq!(expected, computed, "compact, key = {}", hex::encode(&key));
     }
 }
 
@@ -121,3 +167,1424 @@ fn test_xoofff_incremental_io(
 
     assert_eq!(dig0, dig1);
 }
+
+#[cfg(all(feature = "alloc", not(feature = "simd")))]
+#[test_case(32, 0, 0, 64; "key = 32B message = 0B split = 0B digest = 64B")]
+#[test_case(32, 100, 47, 64; "key = 32B message = 100B split = 47B digest = 64B")]
+#[test_case(16, 1024, 513, 200; "key = 16B message = 1024B split = 513B digest = 200B")]
+#[test_case(47, 2048, 2048, 97; "key = 47B message = 2048B split = 2048B digest = 97B")]
+fn test_xoofff_state_export_import(klen: usize, mlen: usize, split: usize, dlen: usize) {
+    use crate::StateError;
+
+    let mut rng = thread_rng();
+
+    let key = random_bytes(klen);
+    let auth_key = random_bytes(32);
+    let msg = random_bytes(mlen);
+
+    let dig0 = xoofff_oneshot(&key, &msg, 0b11, 2, 7, dlen); // digest from uninterrupted deck function
+    let mut dig1 = vec![0u8; dlen]; // digest from resumed deck function
+
+    // checkpoint in the middle of absorption
+    let mut deck1 = Xoofff::new(&key);
+    deck1.absorb(&msg[..split]);
+    let snapshot = deck1.export_state();
+
+    let mut deck1 = Xoofff::import_state(&snapshot).unwrap();
+    deck1.absorb(&msg[split..]);
+    deck1.finalize(0b11, 2, 7);
+
+    // checkpoint in the middle of squeezing, this time authenticated
+    let half = dlen / 2;
+    deck1.squeeze(&mut dig1[..half]);
+    let snapshot = deck1.export_state_authenticated(&auth_key);
+
+    let mut deck1 = Xoofff::import_state_authenticated(&snapshot, &auth_key).unwrap();
+    deck1.squeeze(&mut dig1[half..]);
+
+    assert_eq!(dig0, dig1);
+
+    // tampering with any byte of snapshot or using wrong key must be detected
+    let mut tampered = snapshot.clone();
+    let idx = (rng.next_u32() as usize) % tampered.len();
+    tampered[idx] ^= 1u8 << (rng.next_u32() % 8);
+
+    assert_eq!(
+        Xoofff::import_state_authenticated(&tampered, &auth_key).err(),
+        Some(StateError::AuthenticationFailed)
+    );
+    assert_eq!(
+        Xoofff::import_state_authenticated(&snapshot, &key).err(),
+        Some(StateError::AuthenticationFailed)
+    );
+    assert_eq!(
+        Xoofff::import_state(&snapshot).err(),
+        Some(StateError::InvalidLength)
+    );
+    assert_eq!(
+        Xoofff::import_state(&snapshot[..snapshot.len() - crate::TAG_LEN]).err(),
+        Some(StateError::UnexpectedAuthentication)
+    );
+
+    // finalized state never has buffered message bytes, nor is its output cursor behind skipped
+    // output bytes
+    let mut malformed = deck1.export_state();
+    malformed[7] = 1;
+    assert_eq!(
+        Xoofff::import_state(&malformed).err(),
+        Some(StateError::Malformed)
+    );
+
+    let mut malformed = deck1.export_state();
+    malformed[17..25].copy_from_slice(&u64::MAX.to_le_bytes());
+    assert_eq!(
+        Xoofff::import_state(&malformed).err(),
+        Some(StateError::Malformed)
+    );
+
+    // snapshots of older layouts must be rejected, instead of being parsed with current layout
+    let mut older = deck1.export_state();
+    for version in [1, 2] {
+        older[4] = version;
+        assert_eq!(
+            Xoofff::import_state(&older).err(),
+            Some(StateError::UnsupportedVersion(version))
+        );
+    }
+}
+
+#[cfg(all(feature = "serde", not(feature = "simd")))]
+#[test]
+fn test_xoofff_serde() {
+    let key = random_bytes(32);
+    let msg = random_bytes(100);
+
+    let mut dig0 = [0u8; 64];
+    let mut dig1 = [0u8; 64];
+
+    let mut deck0 = Xoofff::new(&key);
+    deck0.absorb(&msg);
+
+    let encoded = serde_json::to_string(&deck0).unwrap();
+    let mut deck1: Xoofff = serde_json::from_str(&encoded).unwrap();
+
+    deck0.finalize(0, 0, 0);
+    deck0.squeeze(&mut dig0);
+    deck1.finalize(0, 0, 0);
+    deck1.squeeze(&mut dig1);
+
+    assert_eq!(dig0, dig1);
+}
+
+#[cfg(feature = "std")]
+#[test_case(32, 0, 0; "key = 32B message = 0B digest = 0B")]
+#[test_case(32, 100, 64; "key = 32B message = 100B digest = 64B")]
+#[test_case(16, 6144, 6144; "key = 16B message = 6144B digest = 6144B")]
+#[test_case(47, 20001, 13337; "key = 47B message = 20001B digest = 13337B")]
+fn test_xoofff_io(klen: usize, mlen: usize, dlen: usize) {
+    use std::io::{self, Cursor, Read, Write};
+
+    let key = random_bytes(klen);
+    let msg = random_bytes(mlen);
+
+    let dig0 = xoofff_oneshot(&key, &msg, 0, 0, 0, dlen); // digest from absorb/ squeeze API
+    let mut dig1 = vec![0u8; dlen]; // digest from Write/ Read adapters
+    let mut dig2 = Vec::with_capacity(dlen); // digest from reader/ writer helpers
+
+    let mut deck1 = Xoofff::new(&key);
+    let n = io::copy(&mut Cursor::new(&msg), &mut deck1.absorber()).unwrap();
+    assert_eq!(n, mlen as u64);
+    assert!(deck1.squeezer().read(&mut dig1).is_err());
+    deck1.finalize(0, 0, 0);
+    assert!(deck1.absorber().write(&[0xff]).is_err());
+    deck1.squeezer().read_exact(&mut dig1).unwrap();
+
+    let mut deck2 = Xoofff::new(&key);
+    let n = deck2.absorb_reader(Cursor::new(&msg)).unwrap();
+    assert_eq!(n, mlen as u64);
+    deck2.finalize(0, 0, 0);
+    deck2.squeeze_to_writer(&mut dig2, dlen as u64).unwrap();
+
+    assert_eq!(dig0, dig1);
+    assert_eq!(dig0, dig2);
+}
+
+/// Polls given future to completion, on current thread, without any async runtime.
+#[cfg(any(feature = "tokio", feature = "futures-io"))]
+fn block_on<F: core::future::Future>(fut: F) -> F::Output {
+    use core::pin::pin;
+    use core::task::{Context, Poll, Waker};
+
+    let mut fut = pin!(fut);
+    let mut cx = Context::from_waker(Waker::noop());
+
+    loop {
+        if let Poll::Ready(v) = fut.as_mut().poll(&mut cx) {
+            return v;
+        }
+    }
+}
+
+/// Asynchronous reader which hands out message in small, randomly sized chunks, returning
+/// `Poll::Pending` before each chunk, emulating a slow network peer.
+#[cfg(any(feature = "tokio", feature = "futures-io"))]
+struct ChunkyReader<'a> {
+    msg: &'a [u8],
+    pending: bool,
+}
+
+#[cfg(any(feature = "tokio", feature = "futures-io"))]
+impl ChunkyReader<'_> {
+    fn poll_chunk(&mut self, cx: &mut core::task::Context<'_>, buf: &mut [u8]) -> Option<usize> {
+        self.pending = !self.pending;
+        if self.pending {
+            cx.waker().wake_by_ref();
+            return None;
+        }
+
+        let n = cmp::min(
+            cmp::min(buf.len(), self.msg.len()),
+            1 + thread_rng().next_u32() as usize % 97,
+        );
+        buf[..n].copy_from_slice(&self.msg[..n]);
+        self.msg = &self.msg[n..];
+        Some(n)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl tokio::io::AsyncRead for ChunkyReader<'_> {
+    fn poll_read(
+        self: core::pin::Pin<&mut Self>,
+        cx: &mut core::task::Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> core::task::Poll<std::io::Result<()>> {
+        match self.get_mut().poll_chunk(cx, buf.initialize_unfilled()) {
+            Some(n) => {
+                buf.advance(n);
+                core::task::Poll::Ready(Ok(()))
+            }
+            None => core::task::Poll::Pending,
+        }
+    }
+}
+
+#[cfg(feature = "futures-io")]
+impl futures_io::AsyncRead for ChunkyReader<'_> {
+    fn poll_read(
+        self: core::pin::Pin<&mut Self>,
+        cx: &mut core::task::Context<'_>,
+        buf: &mut [u8],
+    ) -> core::task::Poll<std::io::Result<usize>> {
+        match self.get_mut().poll_chunk(cx, buf) {
+            Some(n) => core::task::Poll::Ready(Ok(n)),
+            None => core::task::Poll::Pending,
+        }
+    }
+}
+
+#[cfg(any(feature = "tokio", feature = "futures-io"))]
+#[test_case(32, 0, 32; "key = 32B message = 0B digest = 32B")]
+#[test_case(32, 1000, 64; "key = 32B message = 1000B digest = 64B")]
+#[test_case(47, 20001, 333; "key = 47B message = 20001B digest = 333B")]
+fn test_xoofff_async_io(klen: usize, mlen: usize, dlen: usize) {
+    use core::future::poll_fn;
+    use core::pin::Pin;
+
+    let key = random_bytes(klen);
+    let msg = random_bytes(mlen);
+
+    let dig0 = xoofff_oneshot(&key, &msg, 0, 0, 0, dlen); // digest from absorb/ squeeze API
+
+    #[cfg(feature = "tokio")]
+    {
+        use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+        let mut dig1 = vec![0u8; dlen];
+        let reader = ChunkyReader {
+            msg: &msg,
+            pending: false,
+        };
+        let n = block_on(crate::async_io::tokio::mac_stream(&key, reader, &mut dig1)).unwrap();
+
+        assert_eq!(n, mlen as u64);
+        assert_eq!(dig0, dig1);
+
+        let mut dig2 = vec![0u8; dlen];
+        let mut deck2 = Xoofff::new(&key);
+        for chunk in msg.chunks(13) {
+            let mut absorber = deck2.absorber();
+            let n = block_on(poll_fn(|cx| Pin::new(&mut absorber).poll_write(cx, chunk))).unwrap();
+            assert_eq!(n, chunk.len());
+        }
+        deck2.finalize(0, 0, 0);
+        for chunk in dig2.chunks_mut(7) {
+            let mut squeezer = deck2.squeezer();
+            let mut rbuf = ReadBuf::new(chunk);
+            block_on(poll_fn(|cx| {
+                Pin::new(&mut squeezer).poll_read(cx, &mut rbuf)
+            }))
+            .unwrap();
+            assert!(rbuf.remaining() == 0);
+        }
+
+        assert_eq!(dig0, dig2);
+    }
+
+    #[cfg(feature = "futures-io")]
+    {
+        use futures_io::{AsyncRead, AsyncWrite};
+
+        let mut dig1 = vec![0u8; dlen];
+        let reader = ChunkyReader {
+            msg: &msg,
+            pending: false,
+        };
+        let n = block_on(crate::async_io::futures::mac_stream(
+            &key, reader, &mut dig1,
+        ))
+        .unwrap();
+
+        assert_eq!(n, mlen as u64);
+        assert_eq!(dig0, dig1);
+
+        let mut dig2 = vec![0u8; dlen];
+        let mut deck2 = Xoofff::new(&key);
+        for chunk in msg.chunks(13) {
+            let mut absorber = deck2.absorber();
+            let n = block_on(poll_fn(|cx| Pin::new(&mut absorber).poll_write(cx, chunk))).unwrap();
+            assert_eq!(n, chunk.len());
+        }
+        deck2.finalize(0, 0, 0);
+        for chunk in dig2.chunks_mut(7) {
+            let mut squeezer = deck2.squeezer();
+            let n = block_on(poll_fn(|cx| Pin::new(&mut squeezer).poll_read(cx, chunk))).unwrap();
+            assert_eq!(n, chunk.len());
+        }
+
+        assert_eq!(dig0, dig2);
+    }
+}
+
+#[test_case(32, 100, 0; "key = 32B message = 100B offset = 0B")]
+#[test_case(32, 100, 48; "key = 32B message = 100B offset = 48B")]
+#[test_case(16, 1000, 1000; "key = 16B message = 1000B offset = 1000B")]
+#[test_case(47, 0, 12345; "key = 47B message = 0B offset = 12345B")]
+fn test_xoofff_output_seeking(klen: usize, mlen: usize, offset: usize) {
+    const DLEN: usize = 32768;
+
+    let mut rng = thread_rng();
+
+    let key = random_bytes(klen);
+    let msg = random_bytes(mlen);
+
+    // output, squeezed without any offset
+    let full = xoofff_oneshot(&key, &msg, 0b1, 1, 0, offset + DLEN);
+    let expected = &full[offset..];
+
+    // skipping offset bytes during finalization
+    let mut deck1 = Xoofff::new(&key);
+    deck1.absorb(&msg);
+    deck1.finalize_with_offset(0b1, 1, offset as u64);
+
+    let mut out = vec![0u8; DLEN];
+    deck1.squeeze(&mut out[..100]);
+    deck1.skip(1000);
+    deck1.squeeze(&mut out[1100..2000]);
+    deck1.skip(5000);
+    deck1.squeeze(&mut out[7000..]);
+
+    assert_eq!(expected[..100], out[..100]);
+    assert_eq!(expected[1100..2000], out[1100..2000]);
+    assert_eq!(expected[7000..], out[7000..]);
+
+    // random-access reads, both forward and backward
+    for _ in 0..64 {
+        let pos = rng.next_u32() as usize % DLEN;
+        let len = cmp::min(rng.next_u32() as usize % 1024, DLEN - pos);
+
+        deck1.squeeze_at(pos as u64, &mut out[..len]);
+        assert_eq!(expected[pos..pos + len], out[..len]);
+
+        // squeezing continues from where last random-access read ends
+        let len2 = cmp::min(97, DLEN - pos - len);
+        deck1.squeeze(&mut out[..len2]);
+        assert_eq!(expected[pos + len..pos + len + len2], out[..len2]);
+    }
+}
+
+/// Test that jumping input mask ahead by `n` rolls produces same state as applying
+/// roll_Xc `n` times, sequentially.
+#[test_case(0; "n = 0")]
+#[test_case(383; "n = 383")]
+#[test_case(384; "n = 384")]
+#[test_case(385; "n = 385")]
+#[test_case(4097; "n = 4097")]
+fn test_roll_xc_jump(n: u64) {
+    let mut state = random_state();
+
+    let mut expected = state;
+    for _ in 0..n {
+        crate::rolling::roll_xc(&mut expected);
+    }
+
+    crate::rolling::roll_xc_jump(&mut state, n);
+    assert_eq!(state, expected);
+}
+
+/// Test that absorbing message blocks out of order, while supplying final partial block
+/// separately, produces same output as absorbing whole message sequentially, across
+/// multiple `absorb->finalize->squeeze` cycles.
+#[cfg(not(feature = "simd"))]
+#[test_case(32, 0, 64; "key = 32B message = 0B digest = 64B")]
+#[test_case(32, 48, 64; "key = 32B message = 48B digest = 64B")]
+#[test_case(16, 1000, 100; "key = 16B message = 1000B digest = 100B")]
+#[test_case(47, 20001, 48; "key = 47B message = 20001B digest = 48B")]
+fn test_xoofff_out_of_order_absorption(klen: usize, mlen: usize, dlen: usize) {
+    let mut rng = thread_rng();
+
+    let key = random_bytes(klen);
+
+    let mut deck0 = Xoofff::new(&key);
+    let mut deck1 = Xoofff::new(&key);
+
+    for _ in 0..3 {
+        let msg = random_bytes(mlen);
+
+        deck0.absorb(&msg);
+        deck0.finalize(0, 0, 0);
+
+        let full = msg.len() / 48;
+        let mut idxs = (0..full).collect::<Vec<_>>();
+        for i in (1..idxs.len()).rev() {
+            let j = rng.next_u32() as usize % (i + 1);
+            idxs.swap(i, j);
+        }
+
+        // final partial block is supplied somewhere in between
+        let at = if idxs.is_empty() {
+            0
+        } else {
+            rng.next_u32() as usize % idxs.len()
+        };
+        for (k, &i) in idxs.iter().enumerate() {
+            if k == at {
+                deck1.absorb_final_block_at(full as u64, &msg[full * 48..]);
+            }
+            deck1.absorb_block_at(i as u64, msg[i * 48..(i + 1) * 48].try_into().unwrap());
+        }
+        if idxs.is_empty() {
+            deck1.absorb_final_block_at(full as u64, &msg[full * 48..]);
+        }
+        deck1.finalize(0, 0, 0);
+
+        let mut out0 = vec![0u8; dlen];
+        let mut out1 = vec![0u8; dlen];
+        deck0.squeeze(&mut out0);
+        deck1.squeeze(&mut out1);
+        assert_eq!(out0, out1);
+
+        deck0.restart();
+        deck1.restart();
+    }
+
+    // run of consecutive blocks, absorbed in single call
+    let msg = random_bytes(48 * 40 + 7);
+
+    deck0.absorb(&msg);
+    deck0.finalize(0, 0, 0);
+
+    deck1.absorb_final_block_at(40, &msg[48 * 40..]);
+    deck1.absorb_blocks_at(20, &msg[48 * 20..48 * 40]);
+    deck1.absorb_blocks_at(0, &msg[..48 * 20]);
+    deck1.finalize(0, 0, 0);
+
+    let mut out0 = vec![0u8; dlen];
+    let mut out1 = vec![0u8; dlen];
+    deck0.squeeze(&mut out0);
+    deck1.squeeze(&mut out1);
+    assert_eq!(out0, out1);
+}
+
+/// Test that absorbing message using multiple threads produces same output as absorbing
+/// it sequentially, even when absorption starts at a non-zero offset into message block.
+#[cfg(all(feature = "rayon", not(feature = "simd")))]
+#[test_case(32, 0, 1_000_000; "key = 32B prefix = 0B message = 1000000B")]
+#[test_case(16, 17, 1_000_000; "key = 16B prefix = 17B message = 1000000B")]
+#[test_case(47, 1000, 393_216; "key = 47B prefix = 1000B message = 393216B")]
+#[test_case(47, 5, 10_000; "key = 47B prefix = 5B message = 10000B")]
+fn test_xoofff_absorb_parallel(klen: usize, plen: usize, mlen: usize) {
+    const DLEN: usize = 64;
+
+    let key = random_bytes(klen);
+    let msg = random_bytes(plen + mlen);
+
+    let dig0 = xoofff_oneshot(&key, &msg, 0, 0, 0, DLEN);
+
+    let mut deck1 = Xoofff::new(&key);
+    deck1.absorb(&msg[..plen]);
+    deck1.absorb_parallel(&msg[plen..]);
+    deck1.finalize(0, 0, 0);
+
+    let mut dig1 = vec![0u8; DLEN];
+    deck1.squeeze(&mut dig1);
+
+    assert_eq!(dig0, dig1);
+}
+
+/// Test that squeezing output using multiple threads produces same bytes as squeezing
+/// it sequentially, even when squeezing starts at a non-zero offset into output block.
+#[cfg(all(feature = "rayon", not(feature = "simd")))]
+#[test_case(32, 0, 1_000_000; "key = 32B prefix = 0B digest = 1000000B")]
+#[test_case(16, 17, 1_000_000; "key = 16B prefix = 17B digest = 1000000B")]
+#[test_case(47, 1000, 393_264; "key = 47B prefix = 1000B digest = 393264B")]
+#[test_case(47, 5, 10_000; "key = 47B prefix = 5B digest = 10000B")]
+fn test_xoofff_squeeze_parallel(klen: usize, plen: usize, dlen: usize) {
+    let key = random_bytes(klen);
+    let msg = random_bytes(100);
+
+    let dig0 = xoofff_oneshot(&key, &msg, 0, 0, 0, plen + dlen + 100);
+
+    let mut deck1 = Xoofff::new(&key);
+    deck1.absorb(&msg);
+    deck1.finalize(0, 0, 0);
+
+    let mut dig1 = vec![0u8; plen + dlen + 100];
+
+    deck1.squeeze(&mut dig1[..plen]);
+    deck1.squeeze_parallel(&mut dig1[plen..plen + dlen]);
+    deck1.squeeze(&mut dig1[plen + dlen..]); // squeezing continues from where it ends
+
+    assert_eq!(dig0, dig1);
+}
+
+/// Test that computing MACs over many independent ( key, message ) pairs, in lanes of
+/// parallel permutation, produces same output as computing each of them separately.
+#[test_case(0; "instances = 0")]
+#[test_case(1; "instances = 1")]
+#[test_case(5; "instances = 5")]
+#[test_case(17; "instances = 17")]
+#[test_case(100; "instances = 100")]
+fn test_xoofff_mac_many(cnt: usize) {
+    let mut rng = thread_rng();
+
+    let keys = (0..cnt)
+        .map(|_| random_bytes(rng.next_u32() as usize % 48))
+        .collect::<Vec<_>>();
+    let msgs = (0..cnt)
+        .map(|i| {
+            // mix of empty, block aligned and arbitrary length messages
+            random_bytes(match i % 4 {
+                0 => 0,
+                1 => 48 * (rng.next_u32() as usize % 4),
+                _ => rng.next_u32() as usize % 500,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let mut outs = (0..cnt)
+        .map(|_| vec![0u8; rng.next_u32() as usize % 120])
+        .collect::<Vec<_>>();
+    let expected = keys
+        .iter()
+        .zip(msgs.iter())
+        .zip(outs.iter())
+        .map(|((key, msg), out)| xoofff_oneshot(key, msg, 0, 0, 0, out.len()))
+        .collect::<Vec<_>>();
+
+    let keys = keys.iter().map(|key| key.as_slice()).collect::<Vec<_>>();
+    let msgs = msgs.iter().map(|msg| msg.as_slice()).collect::<Vec<_>>();
+    let mut outs_ref = outs
+        .iter_mut()
+        .map(|out| out.as_mut_slice())
+        .collect::<Vec<_>>();
+
+    Xoofff::mac_many(&keys, &msgs, &mut outs_ref);
+    assert_eq!(outs, expected);
+}
+
+/// Test that XORing squeezed bytes into a buffer produces same result as squeezing into
+/// a temporary buffer and XORing it, for arbitrary sequence of squeeze lengths.
+#[test_case(32, 100, 0; "key = 32B message = 100B ciphertext = 0B")]
+#[test_case(32, 100, 47; "key = 32B message = 100B ciphertext = 47B")]
+#[test_case(16, 1000, 6144; "key = 16B message = 1000B ciphertext = 6144B")]
+#[test_case(47, 0, 20001; "key = 47B message = 0B ciphertext = 20001B")]
+fn test_xoofff_squeeze_xor(klen: usize, mlen: usize, clen: usize) {
+    let mut rng = thread_rng();
+
+    let key = random_bytes(klen);
+    let msg = random_bytes(mlen);
+    let mut txt = random_bytes(clen);
+
+    // key-stream, followed by output to be squeezed after XORing
+    let ks = xoofff_oneshot(&key, &msg, 0b1, 1, 7, clen + 100);
+
+    let expected = txt
+        .iter()
+        .zip(ks.iter())
+        .map(|(t, k)| t ^ k)
+        .collect::<Vec<_>>();
+
+    let mut deck1 = Xoofff::new(&key);
+    deck1.absorb(&msg);
+    deck1.finalize(0b1, 1, 7);
+
+    let mut off = 0;
+    while off < clen {
+        let len = cmp::min(rng.next_u32() as usize % 2000, clen - off);
+        deck1.squeeze_xor(&mut txt[off..off + len]);
+        off += len;
+    }
+    assert_eq!(txt, expected);
+
+    // squeezing continues from where XORing ends
+    let mut out = [0u8; 100];
+    deck1.squeeze(&mut out);
+    assert_eq!(out, ks[clen..]);
+}
+
+/// Test that each 8 -way parallel ( and single state ) Xoodoo permutation, written with `core::arch`
+/// intrinsics, produces same output as scalar permutation, for all instruction sets, executing CPU
+/// supports.
+#[cfg(all(
+    feature = "intrinsics",
+    any(target_arch = "x86", target_arch = "x86_64")
+))]
+#[test_case(1; "rounds = 1")]
+#[test_case(6; "rounds = 6")]
+#[test_case(12; "rounds = 12")]
+fn test_intrinsics_permute(rounds: usize) {
+    match rounds {
+        1 => intrinsics_permute_matches_serial::<1>(),
+        6 => intrinsics_permute_matches_serial::<6>(),
+        _ => intrinsics_permute_matches_serial::<12>(),
+    }
+}
+
+#[cfg(all(
+    feature = "intrinsics",
+    any(target_arch = "x86", target_arch = "x86_64")
+))]
+fn intrinsics_permute_matches_serial<const ROUNDS: usize>() {
+    use crate::intrinsics::{self, PAR_WIDTH};
+    use crate::xoodoo;
+
+    let states: [[u32; 12]; PAR_WIDTH] = core::array::from_fn(|_| random_state());
+
+    let mut expected = states;
+    expected
+        .iter_mut()
+        .for_each(|state| xoodoo::permute::<ROUNDS>(state));
+
+    // SAFETY: each backend is called only after detecting that CPU supports required feature
+    if std::is_x86_feature_detected!("avx2") {
+        let mut computed = states;
+        unsafe { intrinsics::permute_avx2::<ROUNDS>(&mut computed) };
+        assert_eq!(computed, expected, "AVX2 backend");
+    }
+    if std::is_x86_feature_detected!("ssse3") {
+        let mut computed = states;
+        unsafe { intrinsics::permute_ssse3::<ROUNDS>(&mut computed) };
+        assert_eq!(computed, expected, "SSSE3 backend");
+    }
+    if std::is_x86_feature_detected!("sse2") {
+        let mut computed = states;
+        unsafe { intrinsics::permute_sse2::<ROUNDS>(&mut computed) };
+        assert_eq!(computed, expected, "SSE2 backend");
+    }
+
+    let mut computed = states;
+    intrinsics::permute_x8::<ROUNDS>(&mut computed);
+    assert_eq!(computed, expected, "runtime selected backend");
+
+    // single state permutation, keeping planes in 128 -bit registers
+    for (state, expected) in states.iter().zip(expected.iter()) {
+        if std::is_x86_feature_detected!("avx") {
+            let mut computed = *state;
+            unsafe { intrinsics::permute_x1_avx::<ROUNDS>(&mut computed) };
+            assert_eq!(computed, *expected, "single state AVX backend");
+        }
+        if std::is_x86_feature_detected!("ssse3") {
+            let mut computed = *state;
+            unsafe { intrinsics::permute_x1_ssse3::<ROUNDS>(&mut computed) };
+            assert_eq!(computed, *expected, "single state SSSE3 backend");
+        }
+
+        let mut computed = *state;
+        xoodoo::permute_fast::<ROUNDS>(&mut computed);
+        assert_eq!(computed, *expected, "runtime selected single state backend");
+    }
+}
+
+/// Test that Xoodoo permutation, applied on many independent states in lockstep, produces same
+/// output as applying it on each of them, one after another.
+#[test_case(1; "states = 1")]
+#[test_case(2; "states = 2")]
+#[test_case(3; "states = 3")]
+fn test_xoodoo_permute_interleaved(cnt: usize) {
+    use crate::xoodoo;
+
+    let mut states: [[u32; 12]; 3] = core::array::from_fn(|_| random_state());
+
+    let mut expected = states;
+    expected
+        .iter_mut()
+        .for_each(|state| xoodoo::permute::<6>(state));
+
+    match cnt {
+        1 => xoodoo::permute_interleaved::<1, 6>((&mut states[..1]).try_into().unwrap()),
+        2 => xoodoo::permute_interleaved::<2, 6>((&mut states[..2]).try_into().unwrap()),
+        _ => xoodoo::permute_interleaved::<3, 6>(&mut states),
+    }
+
+    assert_eq!(states[..cnt], expected[..cnt]);
+}
+
+/// Test that Xoofff, compressing and expanding blocks with 1, 2 or 3 Xoodoo permutations
+/// interleaved, produces same output as Xoofff, irrespective of which of them is default for
+/// target, s.t. message/ output byte lengths cover both full groups of blocks and remainders.
+#[test_case(1; "ilp = 1")]
+#[test_case(2; "ilp = 2")]
+#[test_case(3; "ilp = 3")]
+fn test_xoofff_ilp(ilp: usize) {
+    match ilp {
+        1 => xoofff_ilp_matches_xoofff::<1>(),
+        2 => xoofff_ilp_matches_xoofff::<2>(),
+        _ => xoofff_ilp_matches_xoofff::<3>(),
+    }
+}
+
+fn xoofff_ilp_matches_xoofff<const ILP: usize>() {
+    use crate::farfalle::{Farfalle, RollXc, RollXe, Xoodoo};
+
+    type XoofffIlp<const ILP: usize> = Farfalle<Xoodoo<6, ILP>, RollXc, RollXe>;
+
+    for len in [0, 47, 48, 96, 97, 144, 1000] {
+        let key = random_bytes(32);
+        let msg = random_bytes(len);
+
+        let expected = xoofff_oneshot(&key, &msg, 0b11, 2, 0, len);
+
+        let mut computed = vec![0u8; len];
+        let mut deck = XoofffIlp::<ILP>::new(&key);
+        deck.absorb(&msg);
+        deck.finalize(0b11, 2, 0);
+        deck.squeeze(&mut computed);
+
+        assert_eq!(computed, expected);
+    }
+}
+
+/// Test that compact Xoofff keeps a state of less than 150 -bytes, while producing same output as
+/// Xoofff, over many `absorb->finalize->squeeze` cycles, absorbing/ squeezing in arbitrary chunks.
+#[test_case(32, 0, 0, 64; "key = 32B message = 0B offset = 0B digest = 64B")]
+#[test_case(16, 47, 48, 100; "key = 16B message = 47B offset = 48B digest = 100B")]
+#[test_case(47, 1000, 5, 1000; "key = 47B message = 1000B offset = 5B digest = 1000B")]
+#[test_case(0, 4097, 10000, 4097; "key = 0B message = 4097B offset = 10000B digest = 4097B")]
+fn test_compact_xoofff(klen: usize, mlen: usize, offset: u64, dlen: usize) {
+    use crate::compact;
+
+    assert!(core::mem::size_of::<compact::Xoofff>() < 150);
+
+    let mut rng = thread_rng();
+
+    let key = random_bytes(klen);
+
+    let mut deck0 = crate::xoofff::Xoofff::new(&key);
+    let mut deck1 = compact::Xoofff::new(&key);
+
+    for _ in 0..3 {
+        let msg = random_bytes(mlen);
+
+        deck0.absorb(&msg);
+
+        let mut off = 0;
+        while off < mlen {
+            let len = cmp::min(rng.next_u32() as usize % 100 + 1, mlen - off);
+            deck1.absorb(&msg[off..off + len]);
+            off += len;
+        }
+
+        let ds = rng.next_u32() as u8;
+        if offset <= 48 {
+            deck0.finalize(ds, 7, offset as usize);
+            deck1.finalize(ds, 7, offset as usize);
+        } else {
+            deck0.finalize_with_offset(ds, 7, offset);
+            deck1.finalize_with_offset(ds, 7, offset);
+        }
+        assert!(deck1.is_finalized());
+
+        let mut dig0 = vec![0u8; dlen];
+        let mut dig1 = vec![0u8; dlen];
+
+        deck0.squeeze(&mut dig0);
+
+        let mut off = 0;
+        while off < dlen {
+            let len = cmp::min(rng.next_u32() as usize % 100 + 1, dlen - off);
+            if rng.next_u32() & 1 == 0 {
+                deck1.squeeze(&mut dig1[off..off + len]);
+            } else {
+                deck1.squeeze_xor(&mut dig1[off..off + len]);
+            }
+            off += len;
+        }
+
+        assert_eq!(dig0, dig1);
+
+        // skipping output bytes
+        deck0.skip(97);
+        deck1.skip(97);
+        let len = cmp::min(100, dlen);
+        deck0.squeeze(&mut dig0[..len]);
+        deck1.squeeze(&mut dig1[..len]);
+        assert_eq!(dig0[..len], dig1[..len]);
+
+        deck0.restart();
+        deck1.restart();
+    }
+}
+
+/// Test that Xoofff, evaluated at compile-time, produces same output as Xoofff, evaluated at
+/// runtime, for message byte lengths around block boundaries and output byte lengths, not being
+/// multiple of block size.
+#[test_case(32, 0; "key = 32B message = 0B")]
+#[test_case(0, 47; "key = 0B message = 47B")]
+#[test_case(16, 48; "key = 16B message = 48B")]
+#[test_case(47, 49; "key = 47B message = 49B")]
+#[test_case(24, 1000; "key = 24B message = 1000B")]
+fn test_xoofff_const(klen: usize, mlen: usize) {
+    use crate::xoofff_const;
+
+    const KEY: [u8; 16] = [0xa5u8; 16];
+    const OUT: [u8; 100] = xoofff_const::<100>(&KEY, b"compile-time domain tag");
+
+    let mut deck = Xoofff::new(&KEY);
+    deck.absorb(b"compile-time domain tag");
+    deck.finalize(0, 0, 0);
+
+    let mut out = [0u8; 100];
+    deck.squeeze(&mut out);
+    assert_eq!(out, OUT);
+
+    let key = random_bytes(klen);
+    let msg = random_bytes(mlen);
+
+    let out = xoofff_oneshot(&key, &msg, 0, 0, 0, 145);
+    assert_eq!(xoofff_const::<145>(&key, &msg), out[..]);
+    assert_eq!(xoofff_const::<0>(&key, &msg), [0u8; 0]);
+}
+
+/// Xoodoo\[1\], without any of the optional routines of `Permutation` implemented, for testing
+/// that Farfalle can be instantiated with an experimental permutation.
+struct ToyPermutation;
+
+impl crate::farfalle::Permutation for ToyPermutation {
+    type State = [u32; 12];
+    type Block = [u8; 48];
+
+    const WIDTH: usize = 48;
+    const ZERO: Self::State = [0u32; 12];
+    const ZERO_BLOCK: Self::Block = [0u8; 48];
+
+    fn permute(state: &mut Self::State) {
+        crate::xoodoo::permute::<1>(state);
+    }
+
+    fn load(bytes: &[u8]) -> Self::State {
+        let mut state = [0u32; 12];
+        for (w, b) in state.iter_mut().zip(bytes.chunks_exact(4)) {
+            *w = u32::from_le_bytes(b.try_into().unwrap());
+        }
+        state
+    }
+
+    fn store(state: &Self::State, bytes: &mut [u8]) {
+        for (w, b) in state.iter().zip(bytes.chunks_exact_mut(4)) {
+            b.copy_from_slice(&w.to_le_bytes());
+        }
+    }
+
+    fn xor(dst: &mut Self::State, src: &Self::State) {
+        dst.iter_mut().zip(src.iter()).for_each(|(d, s)| *d ^= *s);
+    }
+}
+
+/// Test that Farfalle, instantiated with an experimental permutation and rolling functions,
+/// produces same output irrespective of how message is absorbed ( incrementally, in arbitrary
+/// chunks, or block by block, in arbitrary order ) and how output is squeezed ( incrementally,
+/// XORed, or at random positions ), while differing from Xoofff.
+#[test_case(16, 0, 100; "key = 16B message = 0B digest = 100B")]
+#[test_case(32, 48, 48; "key = 32B message = 48B digest = 48B")]
+#[test_case(47, 1000, 1000; "key = 47B message = 1000B digest = 1000B")]
+fn test_farfalle_generic(klen: usize, mlen: usize, dlen: usize) {
+    use crate::farfalle::{Farfalle, RollXc, RollXe, Xoodoo};
+
+    type Toy = Farfalle<ToyPermutation, RollXe, RollXc>;
+    type Xoofff12 = Farfalle<Xoodoo<12>, RollXc, RollXe>;
+
+    let key = random_bytes(klen);
+    let msg = random_bytes(mlen);
+
+    let full = mlen / 48;
+
+    let mut deck0 = Toy::new(&key);
+    let mut deck1 = Toy::new(&key);
+    let mut deck2 = Toy::new(&key);
+
+    deck0.absorb(&msg);
+    for chunk in msg.chunks(7) {
+        deck1.absorb(chunk);
+    }
+    deck2.absorb_final_block_at(full as u64, &msg[full * 48..]);
+    for i in (0..full).rev() {
+        deck2.absorb_block_at(i as u64, msg[i * 48..(i + 1) * 48].try_into().unwrap());
+    }
+
+    deck0.finalize(0b101, 3, 0);
+    deck1.finalize(0b101, 3, 0);
+    deck2.finalize(0b101, 3, 0);
+
+    let mut dig0 = vec![0u8; dlen];
+    let mut dig1 = vec![0u8; dlen];
+    let mut dig2 = vec![0u8; dlen];
+
+    deck0.squeeze(&mut dig0);
+    for chunk in dig1.chunks_mut(13) {
+        deck1.squeeze_xor(chunk);
+    }
+    for (i, chunk) in dig2.chunks_mut(100).enumerate().rev() {
+        deck2.squeeze_at((i * 100) as u64, chunk);
+    }
+
+    assert_eq!(dig0, dig1);
+    assert_eq!(dig0, dig2);
+
+    // same key and message, but different permutation and rolling functions
+    let xoofff = xoofff_oneshot(&key, &msg, 0b101, 3, 0, dlen);
+    let mut xoofff12 = vec![0u8; dlen];
+
+    let mut deck = Xoofff12::new(&key);
+    deck.absorb(&msg);
+    deck.finalize(0b101, 3, 0);
+    deck.squeeze(&mut xoofff12);
+
+    assert_ne!(dig0, xoofff);
+    assert_ne!(xoofff12, xoofff);
+}
+
+/// Rolling function keeping output mask as is, so that every output block is the same one and
+/// seeking arbitrarily far ( i.e. up to `u64::MAX` ) doesn't cost anything.
+struct RollNone;
+
+impl crate::farfalle::RollingFunction<[u32; 12]> for RollNone {
+    fn roll(_: &mut [u32; 12]) {}
+
+    fn jump(_: &mut [u32; 12], _: u64) {}
+}
+
+/// Test that output bytes up to position `u64::MAX` can be read, while seeking any further panics,
+/// instead of silently reading from some other position.
+#[test]
+#[should_panic(expected = "Output position must not overflow u64")]
+fn test_farfalle_output_position_overflow() {
+    use crate::farfalle::{Farfalle, RollXc, Xoodoo};
+
+    type Deck = Farfalle<Xoodoo<6>, RollXc, RollNone>;
+
+    let key = random_bytes(32);
+    let msg = random_bytes(100);
+
+    let mut blk = [0u8; 48];
+
+    let mut deck0 = Deck::new(&key);
+    deck0.absorb(&msg);
+    deck0.finalize(0, 0, 0);
+    deck0.squeeze(&mut blk);
+
+    let mut deck1 = Deck::new(&key);
+    deck1.absorb(&msg);
+    deck1.finalize_with_offset(0, 0, u64::MAX - 10);
+
+    // byte at position `u64::MAX` is 15 -th byte of ( every ) output block
+    let mut out = [0u8; 1];
+    deck1.squeeze_at(10, &mut out);
+    assert_eq!(out[0], blk[15]);
+
+    deck1.squeeze_at(11, &mut out);
+}
+
+/// Test that Keccak-p\[1600, 24\] i.e. Keccak-f\[1600\], applied on all zero state, produces
+/// expected state, see https://github.com/XKCP/XKCP/blob/master/tests/TestVectors/KeccakF-1600-IntermediateValues.txt
+#[test]
+fn test_keccak_f1600_zero_state() {
+    const EXPECTED: [u64; 25] = [
+        0xF1258F7940E1DDE7,
+        0x84D5CCF933C0478A,
+        0xD598261EA65AA9EE,
+        0xBD1547306F80494D,
+        0x8B284E056253D057,
+        0xFF97A42D7F8E6FD4,
+        0x90FEE5A0A44647C4,
+        0x8C5BDA0CD6192E76,
+        0xAD30A6F71B19059C,
+        0x30935AB7D08FFC64,
+        0xEB5AA93F2317D635,
+        0xA9A6E6260D712103,
+        0x81A57C16DBCF555F,
+        0x43B831CD0347C826,
+        0x01F22F1A11A5569F,
+        0x05E5635A21D9AE61,
+        0x64BEFEF28CC970F2,
+        0x613670957BC46611,
+        0xB87C5A554FD00ECB,
+        0x8C3EE88A1CCF32C8,
+        0x940C7922AE3A2614,
+        0x1841F924A2C509E4,
+        0x16F53526E70465C2,
+        0x75F644E97F30A13B,
+        0xEAF1FF7B5CECA249,
+    ];
+
+    let mut state = [0u64; 25];
+    crate::keccak::permute::<24>(&mut state);
+
+    assert_eq!(state, EXPECTED);
+}
+
+/// Test Keccak-p\[1600, 24\] permutation, by computing SHA3-256 digest using it, for messages
+/// spanning one and two sponge blocks, with digests as produced by any FIPS 202 implementation.
+#[test_case(b"", "a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a"; "message = 0B")]
+#[test_case(b"abc", "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532"; "message = 3B")]
+#[test_case(&[b'a'; 200], "cce34485baf2bf2aca99b94833892a4f52896d3d153f7b840cc4f9fe695f1387"; "message = 200B")]
+fn test_keccak_sha3_256(msg: &[u8], expected: &str) {
+    const RATE: usize = 136;
+
+    let mut padded = msg.to_vec();
+    padded.push(0x06);
+    padded.resize(padded.len().next_multiple_of(RATE), 0);
+    *padded.last_mut().unwrap() |= 0x80;
+
+    let mut state = [0u64; 25];
+    for blk in padded.chunks_exact(RATE) {
+        for (lane, b) in state.iter_mut().zip(blk.chunks_exact(8)) {
+            *lane ^= u64::from_le_bytes(b.try_into().unwrap());
+        }
+        crate::keccak::permute::<24>(&mut state);
+    }
+
+    let digest = state[..4]
+        .iter()
+        .flat_map(|lane| lane.to_le_bytes())
+        .collect::<Vec<_>>();
+    assert_eq!(hex::encode(digest), expected);
+}
+
+/// Test that Kravatte produces same output as a straightforward evaluation of Farfalle, following
+/// algorithm 1 of https://ia.cr/2016/1188, with Keccak-p\[1600, 6\], roll_c and roll_e, one block
+/// at a time, irrespective of how message is absorbed and output is squeezed.
+#[test_case(16, 0, 100; "key = 16B message = 0B digest = 100B")]
+#[test_case(199, 200, 200; "key = 199B message = 200B digest = 200B")]
+#[test_case(32, 1000, 1000; "key = 32B message = 1000B digest = 1000B")]
+fn test_kravatte(klen: usize, mlen: usize, dlen: usize) {
+    use crate::keccak;
+    use crate::kravatte::{roll_c, roll_e};
+    use crate::Kravatte;
+
+    fn to_lanes(bytes: &[u8]) -> [u64; 25] {
+        let mut lanes = [0u64; 25];
+        for (lane, b) in lanes.iter_mut().zip(bytes.chunks_exact(8)) {
+            *lane = u64::from_le_bytes(b.try_into().unwrap());
+        }
+        lanes
+    }
+
+    let key = random_bytes(klen);
+    let msg = random_bytes(mlen);
+
+    // masked key derivation
+    let mut padded_key = key.clone();
+    padded_key.push(0x01);
+    padded_key.resize(200, 0);
+
+    let mut k = to_lanes(&padded_key);
+    keccak::permute::<6>(&mut k);
+
+    // compression, s.t. message is padded using domain seperator 0b11, of bit width 2
+    let mut padded_msg = msg.clone();
+    padded_msg.push(0b111);
+    padded_msg.resize(padded_msg.len().next_multiple_of(200), 0);
+
+    let mut x = [0u64; 25];
+    for blk in padded_msg.chunks_exact(200) {
+        let mut state = to_lanes(blk);
+        state.iter_mut().zip(k.iter()).for_each(|(s, k)| *s ^= *k);
+        keccak::permute::<6>(&mut state);
+        x.iter_mut().zip(state.iter()).for_each(|(x, s)| *x ^= *s);
+
+        roll_c(&mut k);
+    }
+    roll_c(&mut k);
+
+    // expansion
+    let mut y = x;
+    keccak::permute::<6>(&mut y);
+
+    let mut expected = Vec::new();
+    while expected.len() < dlen {
+        let mut z = y;
+        keccak::permute::<6>(&mut z);
+        z.iter()
+            .zip(k.iter())
+            .for_each(|(z, k)| expected.extend_from_slice(&(z ^ k).to_le_bytes()));
+
+        roll_e(&mut y);
+    }
+    expected.truncate(dlen);
+
+    let mut deck0 = Kravatte::new(&key);
+    let mut deck1 = Kravatte::new(&key);
+
+    deck0.absorb(&msg);
+    for chunk in msg.chunks(33) {
+        deck1.absorb(chunk);
+    }
+
+    deck0.finalize(0b11, 2, 0);
+    deck1.finalize(0b11, 2, 0);
+
+    let mut dig0 = vec![0u8; dlen];
+    let mut dig1 = vec![0u8; dlen];
+
+    deck0.squeeze(&mut dig0);
+    for chunk in dig1.chunks_mut(77) {
+        deck1.squeeze_xor(chunk);
+    }
+
+    assert_eq!(dig0, expected);
+    assert_eq!(dig1, expected);
+
+    // restarting, for absorbing another message, keeps input mask and accumulator
+    deck0.restart();
+    deck0.absorb(&msg);
+    deck0.finalize(0b11, 2, 0);
+    deck0.squeeze(&mut dig0);
+
+    assert_ne!(dig0, expected);
+}
+
+/// Test functional correctness of Kravatte deck function, by using known answer tests, generated
+/// using Kravatte Achouffe implementation of XKCP i.e. `Kravatte_MaskDerivation` with key, `Kra`
+/// with whole message and `KRAVATTE_FLAG_LAST`, followed by `Vatte`, s.t. each known answer test is
+/// `Key`, `Msg` and `Out` lines, carrying hex encoded bytes. They must cover messages and outputs
+/// spanning multiple 200 -bytes blocks.
+#[test]
+fn test_kravatte_kat() {
+    use crate::Kravatte;
+
+    let kats = read_kats("./kats/Kravatte_KAT.txt", 3);
+
+    assert!(kats.iter().any(|kat| kat[1].len() / 2 > 2 * 200));
+    assert!(kats.iter().any(|kat| kat[2].len() / 2 > 2 * 200));
+
+    for kat in kats {
+        let key = hex::decode(&kat[0]).unwrap();
+        let msg = hex::decode(&kat[1]).unwrap();
+        let expected = hex::decode(&kat[2]).unwrap();
+
+        let mut computed = vec![0u8; expected.len()];
+
+        let mut deck = Kravatte::new(&key);
+        deck.absorb(&msg);
+        deck.finalize(0, 0, 0);
+        deck.squeeze(&mut computed);
+
+        assert_eq!(
+            expected,
+            computed,
+            "key = {}, msg = {}",
+            hex::encode(&key),
+            hex::encode(&msg)
+        );
+    }
+}
+
+/// Test that Xoofff with per-phase # -of rounds produces same output as a straightforward
+/// evaluation of Farfalle, following algorithm 1 of https://ia.cr/2016/1188, with Xoodoo\[B\] for
+/// mask derivation, Xoodoo\[C\] for compression, Xoodoo\[D\] for the middle and Xoodoo\[E\] for
+/// expansion, while standard parameter set is same as Xoofff.
+#[test_case(6, 6, 6, 6; "rounds = (6, 6, 6, 6)")]
+#[test_case(12, 12, 12, 12; "rounds = (12, 12, 12, 12)")]
+#[test_case(6, 1, 6, 1; "rounds = (6, 1, 6, 1)")]
+#[test_case(1, 6, 12, 4; "rounds = (1, 6, 12, 4)")]
+fn test_xoofff_params(b: usize, c: usize, d: usize, e: usize) {
+    match (b, c, d, e) {
+        (6, 6, 6, 6) => xoofff_params_matches_reference::<6, 6, 6, 6>(),
+        (12, 12, 12, 12) => xoofff_params_matches_reference::<12, 12, 12, 12>(),
+        (6, 1, 6, 1) => xoofff_params_matches_reference::<6, 1, 6, 1>(),
+        _ => xoofff_params_matches_reference::<1, 6, 12, 4>(),
+    }
+}
+
+fn xoofff_params_matches_reference<
+    const B: usize,
+    const C: usize,
+    const D: usize,
+    const E: usize,
+>() {
+    use crate::rolling::{roll_xc, roll_xe};
+    use crate::xoodoo;
+    use crate::XoofffParams;
+
+    fn to_words(bytes: &[u8]) -> [u32; 12] {
+        let mut words = [0u32; 12];
+        for (word, b) in words.iter_mut().zip(bytes.chunks_exact(4)) {
+            *word = u32::from_le_bytes(b.try_into().unwrap());
+        }
+        words
+    }
+
+    let key = random_bytes(32);
+    let msg = random_bytes(1000);
+
+    // masked key derivation
+    let mut padded_key = key.clone();
+    padded_key.push(0x01);
+    padded_key.resize(48, 0);
+
+    let mut k = to_words(&padded_key);
+    xoodoo::permute::<B>(&mut k);
+
+    // compression, s.t. message is padded using domain seperator 0b101, of bit width 3
+    let mut padded_msg = msg.clone();
+    padded_msg.push(0b1101);
+    padded_msg.resize(padded_msg.len().next_multiple_of(48), 0);
+
+    let mut x = [0u32; 12];
+    for blk in padded_msg.chunks_exact(48) {
+        let mut state = to_words(blk);
+        state.iter_mut().zip(k.iter()).for_each(|(s, k)| *s ^= *k);
+        xoodoo::permute::<C>(&mut state);
+        x.iter_mut().zip(state.iter()).for_each(|(x, s)| *x ^= *s);
+
+        roll_xc(&mut k);
+    }
+    roll_xc(&mut k);
+
+    // middle
+    let mut y = x;
+    xoodoo::permute::<D>(&mut y);
+
+    // expansion
+    let mut expected = Vec::new();
+    while expected.len() < 500 {
+        let mut z = y;
+        xoodoo::permute::<E>(&mut z);
+        z.iter()
+            .zip(k.iter())
+            .for_each(|(z, k)| expected.extend_from_slice(&(z ^ k).to_le_bytes()));
+
+        roll_xe(&mut y);
+    }
+    expected.truncate(500);
+
+    let mut deck = XoofffParams::<B, C, D, E>::new(&key);
+    for chunk in msg.chunks(33) {
+        deck.absorb(chunk);
+    }
+    deck.finalize(0b101, 3, 0);
+
+    let mut dig = vec![0u8; 500];
+    deck.squeeze(&mut dig);
+    assert_eq!(dig, expected);
+
+    let xoofff = xoofff_oneshot(&key, &msg, 0b101, 3, 0, 500);
+
+    let standard = (B, C, D, E) == (6, 6, 6, 6);
+    assert_eq!(dig == xoofff, standard);
+}
+
+/// Test that inverse Xoodoo permutation undoes forward permutation ( and vice versa ), for all
+/// possible # -of rounds, while forward permutation isn't an identity.
+#[test]
+fn test_xoodoo_permute_inverse() {
+    xoodoo_permute_inverse_roundtrip::<0>();
+    xoodoo_permute_inverse_roundtrip::<1>();
+    xoodoo_permute_inverse_roundtrip::<2>();
+    xoodoo_permute_inverse_roundtrip::<3>();
+    xoodoo_permute_inverse_roundtrip::<4>();
+    xoodoo_permute_inverse_roundtrip::<5>();
+    xoodoo_permute_inverse_roundtrip::<6>();
+    xoodoo_permute_inverse_roundtrip::<7>();
+    xoodoo_permute_inverse_roundtrip::<8>();
+    xoodoo_permute_inverse_roundtrip::<9>();
+    xoodoo_permute_inverse_roundtrip::<10>();
+    xoodoo_permute_inverse_roundtrip::<11>();
+    xoodoo_permute_inverse_roundtrip::<12>();
+}
+
+fn xoodoo_permute_inverse_roundtrip<const ROUNDS: usize>() {
+    use crate::xoodoo;
+
+    for _ in 0..16 {
+        let state = random_state();
+
+        let mut fwd = state;
+        xoodoo::permute::<ROUNDS>(&mut fwd);
+        assert_eq!(fwd == state, ROUNDS == 0);
+
+        let mut computed = fwd;
+        xoodoo::permute_inverse::<ROUNDS>(&mut computed);
+        assert_eq!(computed, state);
+
+        let mut computed = state;
+        xoodoo::permute_inverse::<ROUNDS>(&mut computed);
+        xoodoo::permute::<ROUNDS>(&mut computed);
+        assert_eq!(computed, state);
+    }
+}
+
+/// Test that each "SnP" operation of Xoodoo permutation state packs bytes into lanes in
+/// little-endian order and agrees with byte oriented evaluation of it, while permuting state
+/// with any # -of rounds is same as applying Xoodoo\[n_r\] on lanes.
+#[test_case(0, 48; "offset = 0B length = 48B")]
+#[test_case(5, 23; "offset = 5B length = 23B")]
+#[test_case(47, 1; "offset = 47B length = 1B")]
+#[test_case(12, 0; "offset = 12B length = 0B")]
+fn test_xoodoo_state(offset: usize, len: usize) {
+    use crate::xoodoo;
+    use crate::XoodooState;
+
+    fn to_bytes(state: &XoodooState) -> [u8; 48] {
+        let mut bytes = [0u8; 48];
+        for (b, lane) in bytes.chunks_exact_mut(4).zip(state.lanes.iter()) {
+            b.copy_from_slice(&lane.to_le_bytes());
+        }
+        bytes
+    }
+
+    let init: [u8; 48] = random_bytes(48).try_into().unwrap();
+    let data = random_bytes(len);
+
+    let mut state = XoodooState::new();
+    assert_eq!(to_bytes(&state), [0u8; 48]);
+
+    // little-endian lane packing
+    state.overwrite_bytes(0, &init);
+    for (i, lane) in state.lanes.iter().enumerate() {
+        assert_eq!(
+            *lane,
+            u32::from_le_bytes(init[4 * i..4 * (i + 1)].try_into().unwrap())
+        );
+    }
+
+    let mut expected = init;
+
+    state.add_bytes(offset, &data);
+    expected[offset..offset + len]
+        .iter_mut()
+        .zip(data.iter())
+        .for_each(|(e, d)| *e ^= *d);
+    assert_eq!(to_bytes(&state), expected);
+
+    if offset < 48 {
+        state.add_byte(0xa5, offset);
+        expected[offset] ^= 0xa5;
+        assert_eq!(to_bytes(&state), expected);
+    }
+
+    let mut out = vec![0u8; len];
+    state.extract_bytes(offset, &mut out);
+    assert_eq!(out, &expected[offset..offset + len]);
+
+    let mut out = vec![0u8; len];
+    state.extract_and_add_bytes(offset, &data, &mut out);
+    for i in 0..len {
+        assert_eq!(out[i], expected[offset + i] ^ data[i]);
+    }
+
+    state.overwrite_bytes(offset, &data);
+    expected[offset..offset + len].copy_from_slice(&data);
+    assert_eq!(to_bytes(&state), expected);
+
+    state.overwrite_with_zeroes(offset + len);
+    expected[..offset + len].fill(0);
+    assert_eq!(to_bytes(&state), expected);
+
+    for rounds in 0..=12 {
+        let mut lanes = state.lanes;
+        xoodoo::permute_nrounds(&mut lanes, rounds);
+
+        let mut permuted = XoodooState::new();
+        permuted.overwrite_bytes(0, &to_bytes(&state));
+        permuted.permute_nrounds(rounds);
+        assert_eq!(permuted.lanes, lanes);
+    }
+
+    let mut lanes = state.lanes;
+    xoodoo::permute::<6>(&mut lanes);
+    state.permute_nrounds(6);
+    assert_eq!(state.lanes, lanes);
+
+    state.initialize();
+    assert_eq!(to_bytes(&state), [0u8; 48]);
+}
+
+/// Test Xoodoo\[n_r\] permutation, through Xoodoo permutation state, by using known answer tests,
+/// generated with XKCP's Xoodoo "SnP" ( i.e. `Xoodoo_Initialize`, `Xoodoo_AddBytes`,
+/// `Xoodoo_Permute_Nrounds`, `Xoodoo_ExtractBytes` ), for 6 and 12 rounds.
+#[test]
+fn test_xoodoo_state_kat() {
+    use crate::XoodooState;
+
+    for kat in read_kats("./kats/Xoodoo_KAT.txt", 3) {
+        // # -of rounds to apply
+        let rounds = kat[0].parse::<usize>().unwrap();
+        // bytes to be added to all zero state, before permuting it
+        let input = hex::decode(&kat[1]).unwrap();
+        // expected permuted state bytes
+        let expected = hex::decode(&kat[2]).unwrap();
+
+        let mut state = XoodooState::new();
+        state.add_bytes(0, &input);
+        state.permute_nrounds(rounds);
+
+        let mut computed = [0u8; XoodooState::WIDTH];
+        state.extract_bytes(0, &mut computed);
+
+        assert_eq!(
+            expected,
+            computed,
+            "rounds = {}, state = {}",
+            rounds,
+            hex::encode(&input)
+        );
+    }
+}
+
+/// Test that asking Xoodoo permutation state for more rounds than there're round constants panics,
+/// in release builds too.
+#[test]
+#[should_panic]
+fn test_xoodoo_state_too_many_rounds() {
+    let mut state = crate::XoodooState::new();
+    state.permute_nrounds(13);
+}
+
+/// Test that masked key derivation of Xoofff, written using "SnP" operations of Xoodoo
+/// permutation state, produces same input mask as Xoofff itself.
+#[test]
+fn test_xoodoo_state_masked_key() {
+    use crate::XoodooState;
+
+    let key = random_bytes(32);
+
+    let mut state = XoodooState::new();
+    state.add_bytes(0, &key);
+    state.add_byte(0x01, key.len());
+    state.permute_nrounds(6);
+
+    let deck = crate::xoofff::Xoofff::new(&key);
+    assert_eq!(state.lanes, deck.imask);
+}
@@ -3,10 +3,10 @@ use crunchy::unroll;
 /// Maximum number of rounds one can request to have when applying Xoodoo\[n_r\] permutation i.e. n_r <= MAX_ROUNDS
 ///
 /// See table 2 of https://ia.cr/2018/767
-const MAX_ROUNDS: usize = 12;
+pub(crate) const MAX_ROUNDS: usize = 12;
 
 /// Xoodoo\[n_r\] round constants, taken from table 2 of https://ia.cr/2018/767
-const RC: [u32; MAX_ROUNDS] = [
+pub(crate) const RC: [u32; MAX_ROUNDS] = [
     0x00000058, 0x00000038, 0x000003c0, 0x000000d0, 0x00000120, 0x00000014, 0x00000060, 0x0000002c,
     0x00000380, 0x000000f0, 0x000001a0, 0x00000012,
 ];
@@ -18,7 +18,7 @@ const RC: [u32; MAX_ROUNDS] = [
 /// Note, at bit index z = 0, least significant bit of each lane lives.
 /// See row 2 of table 1 of https://ia.cr/2018/767.
 #[inline(always)]
-pub fn cyclic_shift<const T: usize, const V: u32>(plane: &[u32]) -> [u32; 4] {
+pub const fn cyclic_shift<const T: usize, const V: u32>(plane: &[u32]) -> [u32; 4] {
     debug_assert!(
         plane.len() == 4,
         "Each lane of Xoodoo permutation state must have four lanes !"
@@ -33,9 +33,30 @@ pub fn cyclic_shift<const T: usize, const V: u32>(plane: &[u32]) -> [u32; 4] {
     shifted
 }
 
+/// Returns a copy of `idx` -th plane of Xoodoo permutation state. Same as `state[4 * idx..4 * (idx + 1)]`,
+/// but usable in const contexts, where range indexing isn't.
+#[inline(always)]
+pub(crate) const fn plane(state: &[u32], idx: usize) -> [u32; 4] {
+    [
+        state[4 * idx],
+        state[4 * idx + 1],
+        state[4 * idx + 2],
+        state[4 * idx + 3],
+    ]
+}
+
+/// Overwrites `idx` -th plane of Xoodoo permutation state with given plane.
+#[inline(always)]
+pub(crate) const fn set_plane(state: &mut [u32], idx: usize, plane: &[u32; 4]) {
+    state[4 * idx] = plane[0];
+    state[4 * idx + 1] = plane[1];
+    state[4 * idx + 2] = plane[2];
+    state[4 * idx + 3] = plane[3];
+}
+
 /// θ step mapping of Xoodoo permutation, as described in algorithm 1 of https://ia.cr/2018/767.
 #[inline(always)]
-fn theta(state: &mut [u32]) {
+const fn theta(state: &mut [u32]) {
     debug_assert!(
         state.len() == 12,
         "Xoodoo permutation state must have 12 lanes !"
@@ -43,11 +64,8 @@ fn theta(state: &mut [u32]) {
 
     let mut p = [0u32; 4];
     unroll! {
-        for i in (0..12).step_by(4) {
-            p[0] ^= state[i + 0];
-            p[1] ^= state[i + 1];
-            p[2] ^= state[i + 2];
-            p[3] ^= state[i + 3];
+        for i in 0..4 {
+            p[i] = state[i] ^ state[4 + i] ^ state[8 + i];
         }
     }
 
@@ -62,48 +80,47 @@ fn theta(state: &mut [u32]) {
     }
 
     unroll! {
-        for i in (0..12).step_by(4) {
-            state[i + 0] ^= e[0];
-            state[i + 1] ^= e[1];
-            state[i + 2] ^= e[2];
-            state[i + 3] ^= e[3];
+        for i in 0..4 {
+            state[i] ^= e[i];
+            state[4 + i] ^= e[i];
+            state[8 + i] ^= e[i];
         }
     }
 }
 
 /// ρ_west step mapping function of Xoodoo permutation, as described in algorithm 1 of https://ia.cr/2018/767.
 #[inline(always)]
-fn rho_west(state: &mut [u32]) {
+const fn rho_west(state: &mut [u32]) {
     debug_assert!(
         state.len() == 12,
         "Xoodoo permutation state must have 12 lanes !"
     );
 
-    let t0 = cyclic_shift::<1, 0>(&state[4..8]);
-    let t1 = cyclic_shift::<0, 11>(&state[8..12]);
+    let t0 = cyclic_shift::<1, 0>(&plane(state, 1));
+    let t1 = cyclic_shift::<0, 11>(&plane(state, 2));
 
-    state[4..8].copy_from_slice(&t0);
-    state[8..12].copy_from_slice(&t1);
+    set_plane(state, 1, &t0);
+    set_plane(state, 2, &t1);
 }
 
 /// ρ_east step mapping function of Xoodoo permutation, as described in algorithm 1 of https://ia.cr/2018/767.
 #[inline(always)]
-fn rho_east(state: &mut [u32]) {
+const fn rho_east(state: &mut [u32]) {
     debug_assert!(
         state.len() == 12,
         "Xoodoo permutation state must have 12 lanes !"
     );
 
-    let t0 = cyclic_shift::<0, 1>(&state[4..8]);
-    let t1 = cyclic_shift::<2, 8>(&state[8..12]);
+    let t0 = cyclic_shift::<0, 1>(&plane(state, 1));
+    let t1 = cyclic_shift::<2, 8>(&plane(state, 2));
 
-    state[4..8].copy_from_slice(&t0);
-    state[8..12].copy_from_slice(&t1);
+    set_plane(state, 1, &t0);
+    set_plane(state, 2, &t1);
 }
 
 /// ι step mapping function of Xoodoo permutation, as described in algorithm 1 of https://ia.cr/2018/767.
 #[inline(always)]
-fn iota(state: &mut [u32], ridx: usize) {
+const fn iota(state: &mut [u32], ridx: usize) {
     debug_assert!(
         state.len() == 12,
         "Xoodoo permutation state must have 12 lanes !"
@@ -114,7 +131,7 @@ fn iota(state: &mut [u32], ridx: usize) {
 
 /// χ step mapping function of Xoodoo permutation, as described in algorithm 1 of https://ia.cr/2018/767.
 #[inline(always)]
-fn chi(state: &mut [u32]) {
+const fn chi(state: &mut [u32]) {
     debug_assert!(
         state.len() == 12,
         "Xoodoo permutation state must have 12 lanes !"
@@ -152,7 +169,7 @@ fn chi(state: &mut [u32]) {
 
 /// Round function of Xoodoo permutation, as described in algorithm 1 of https://ia.cr/2018/767.
 #[inline(always)]
-fn round(state: &mut [u32], ridx: usize) {
+const fn round(state: &mut [u32], ridx: usize) {
     debug_assert!(
         state.len() == 12,
         "Xoodoo permutation state must have 12 lanes !"
@@ -166,21 +183,188 @@ fn round(state: &mut [u32], ridx: usize) {
     rho_east(state);
 }
 
+/// Inverse of θ step mapping of Xoodoo permutation. θ adds E(P) to each plane, where P is column
+/// parity and E(P) = P shifted by (1, 5) + P shifted by (1, 14), so column parity itself is mapped
+/// to (1 + E)(P). As E^32 = 0, (1 + E)^-1 = (1 + E)(1 + E^2)(1 + E^4)(1 + E^8)(1 + E^16) s.t.
+/// E^(2^k) is P shifted by (2^k, 5 * 2^k) + P shifted by (2^k, 14 * 2^k). Original column parity
+/// is recovered that way, from which E(P) is recomputed and added to each plane, once again.
+#[inline(always)]
+const fn theta_inverse(state: &mut [u32]) {
+    debug_assert!(
+        state.len() == 12,
+        "Xoodoo permutation state must have 12 lanes !"
+    );
+
+    let mut p = [0u32; 4];
+    unroll! {
+        for i in 0..4 {
+            p[i] = state[i] ^ state[4 + i] ^ state[8 + i];
+        }
+    }
+
+    let p = add_shifted::<1, 5, 14>(&p);
+    let p = add_shifted::<2, 10, 28>(&p);
+    let p = add_shifted::<0, 20, 24>(&p);
+    let p = add_shifted::<0, 8, 16>(&p);
+    let p = add_shifted::<0, 16, 0>(&p);
+
+    let t0 = cyclic_shift::<1, 5>(&p);
+    let t1 = cyclic_shift::<1, 14>(&p);
+
+    unroll! {
+        for i in 0..4 {
+            let e = t0[i] ^ t1[i];
+
+            state[i] ^= e;
+            state[4 + i] ^= e;
+            state[8 + i] ^= e;
+        }
+    }
+}
+
+/// Given a plane P, computes P + P shifted by (T, V0) + P shifted by (T, V1), used for inverting θ.
+#[inline(always)]
+const fn add_shifted<const T: usize, const V0: u32, const V1: u32>(plane: &[u32; 4]) -> [u32; 4] {
+    let t0 = cyclic_shift::<T, V0>(plane);
+    let t1 = cyclic_shift::<T, V1>(plane);
+
+    let mut res = [0u32; 4];
+    unroll! {
+        for i in 0..4 {
+            res[i] = plane[i] ^ t0[i] ^ t1[i];
+        }
+    }
+    res
+}
+
+/// Inverse of ρ_west step mapping function of Xoodoo permutation.
+#[inline(always)]
+const fn rho_west_inverse(state: &mut [u32]) {
+    debug_assert!(
+        state.len() == 12,
+        "Xoodoo permutation state must have 12 lanes !"
+    );
+
+    let t0 = cyclic_shift::<3, 0>(&plane(state, 1));
+    let t1 = cyclic_shift::<0, 21>(&plane(state, 2));
+
+    set_plane(state, 1, &t0);
+    set_plane(state, 2, &t1);
+}
+
+/// Inverse of ρ_east step mapping function of Xoodoo permutation.
+#[inline(always)]
+const fn rho_east_inverse(state: &mut [u32]) {
+    debug_assert!(
+        state.len() == 12,
+        "Xoodoo permutation state must have 12 lanes !"
+    );
+
+    let t0 = cyclic_shift::<0, 31>(&plane(state, 1));
+    let t1 = cyclic_shift::<2, 24>(&plane(state, 2));
+
+    set_plane(state, 1, &t0);
+    set_plane(state, 2, &t1);
+}
+
+/// Inverse of round function of Xoodoo permutation, applying inverse of each step mapping, in
+/// reverse order. χ, acting on 3 -bit columns, is an involution, so it's its own inverse, same
+/// goes for ι.
+#[inline(always)]
+const fn round_inverse(state: &mut [u32], ridx: usize) {
+    debug_assert!(
+        state.len() == 12,
+        "Xoodoo permutation state must have 12 lanes !"
+    );
+    debug_assert!(ridx < MAX_ROUNDS, "Round index must ∈ [0, MAX_ROUNDS) !");
+
+    rho_east_inverse(state);
+    chi(state);
+    iota(state, ridx);
+    rho_west_inverse(state);
+    theta_inverse(state);
+}
+
 /// Xoodoo\[n_r\] permutation function s.t. n_r ( <= MAX_ROUNDS ) times round function
 /// is applied on permutation state, as described in algorithm 1 of https://ia.cr/2018/767.
+///
+/// It's a `const fn`, computed using only scalar 32 -bit operations, so that it can be
+/// evaluated at compile-time, see `permute_fast` for its runtime counterpart.
 #[inline(always)]
-pub fn permute<const ROUNDS: usize>(state: &mut [u32]) {
+pub const fn permute<const ROUNDS: usize>(state: &mut [u32]) {
+    permute_nrounds(state, ROUNDS);
+}
+
+/// Xoodoo\[n_r\] permutation function, same as `permute`, but s.t. n_r ( <= MAX_ROUNDS ) is known
+/// only at runtime i.e. last n_r round constants are used, same as Xoodoo_Permute_Nrounds of XKCP.
+///
+/// Panics if n_r > MAX_ROUNDS, even in release builds, as there're no round constants for them.
+#[inline(always)]
+pub const fn permute_nrounds(state: &mut [u32], rounds: usize) {
     debug_assert!(
         state.len() == 12,
         "Xoodoo permutation state must have 12 lanes !"
     );
+    assert!(
+        rounds <= MAX_ROUNDS,
+        "Requested rounds must be <= MAX_ROUNDS !"
+    );
+
+    let mut ridx = MAX_ROUNDS - rounds;
+    while ridx < MAX_ROUNDS {
+        round(state, ridx);
+        ridx += 1;
+    }
+}
+
+/// Inverse of Xoodoo\[n_r\] permutation function s.t. n_r ( <= MAX_ROUNDS ) times inverse round
+/// function is applied on permutation state, in reverse order of rounds, so that
+/// `permute_inverse::<n_r>` undoes `permute::<n_r>`. Useful for cryptanalysis and for building
+/// Even-Mansour style block ciphers over Xoodoo.
+#[allow(dead_code)] // not used by deck function, only exposed with `dev` feature
+#[inline(always)]
+pub const fn permute_inverse<const ROUNDS: usize>(state: &mut [u32]) {
     debug_assert!(
-        ROUNDS <= MAX_ROUNDS,
-        "Requested rounds must be < MAX_ROUNDS !"
+        state.len() == 12,
+        "Xoodoo permutation state must have 12 lanes !"
     );
+    const { assert!(ROUNDS <= MAX_ROUNDS, "Xoodoo rounds must be <= 12") };
+
+    let mut ridx = MAX_ROUNDS;
+    while ridx > MAX_ROUNDS - ROUNDS {
+        ridx -= 1;
+        round_inverse(state, ridx);
+    }
+}
+
+/// Xoodoo\[n_r\] permutation, producing same output as `permute`, but keeping planes in 128 -bit
+/// registers, when `intrinsics` feature is enabled and executing CPU supports SSSE3. Used by
+/// deck function, at runtime.
+#[inline(always)]
+pub(crate) fn permute_fast<const ROUNDS: usize>(state: &mut [u32]) {
+    #[cfg(all(
+        feature = "intrinsics",
+        any(target_arch = "x86", target_arch = "x86_64")
+    ))]
+    if crate::intrinsics::permute_x1::<ROUNDS>(state.try_into().unwrap()) {
+        return;
+    }
+
+    permute::<ROUNDS>(state);
+}
+
+/// Xoodoo\[n_r\] permutation, applied on N independent states in lockstep i.e. i -th round is
+/// applied on each of them, before moving to (i + 1) -th round. Rounds of different states don't
+/// depend on each other, so that superscalar CPUs can execute them in parallel, which helps on
+/// targets where no SIMD implementation is available.
+#[inline(always)]
+pub fn permute_interleaved<const N: usize, const ROUNDS: usize>(states: &mut [[u32; 12]; N]) {
+    const { assert!(ROUNDS <= MAX_ROUNDS, "Xoodoo rounds must be <= 12") };
 
     let start = MAX_ROUNDS - ROUNDS;
     for ridx in start..MAX_ROUNDS {
-        round(state, ridx);
+        for state in states.iter_mut() {
+            round(state, ridx);
+        }
     }
 }
@@ -1,7 +1,15 @@
+use crate::farfalle::{self, Farfalle, RollXc, RollXe, Xoodoo};
 use crate::rolling;
+use crate::snapshot::{self, StateError, STATE_LEN, TAG_LEN};
 use crate::xoodoo;
+#[cfg(any(feature = "alloc", feature = "rayon"))]
+use alloc::vec::Vec;
+#[cfg(feature = "rayon")]
+use core::cmp;
+use core::marker::PhantomData;
 use crunchy::unroll;
-use std::cmp;
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
 
 /// Xoodoo\[n_r\] being a 384 -bit permutation, messages are consumed in 48 -bytes chunks
 const BLOCK_SIZE: usize = 48;
@@ -10,7 +18,12 @@ const BLOCK_SIZE: usize = 48;
 const ROUNDS: usize = 6;
 
 /// \# -of lanes ( each of 32 -bit width ) in Xoodoo permutation state
-const LANE_CNT: usize = BLOCK_SIZE / std::mem::size_of::<u32>();
+const LANE_CNT: usize = BLOCK_SIZE / core::mem::size_of::<u32>();
+
+/// \# -of message/ output blocks processed by a single rayon task, when absorbing/ squeezing
+/// in parallel, large enough for amortizing cost of jumping masks ahead and scheduling the task.
+#[cfg(feature = "rayon")]
+const PAR_CHUNK_BLOCKS: usize = 4096;
 
 /// Xoofff is a deck function, obtained by instantiating Farfalle construction with
 /// Xoodoo\[6\] permutation and two rolling functions, having nice incremental input/
@@ -26,251 +39,487 @@ const LANE_CNT: usize = BLOCK_SIZE / std::mem::size_of::<u32>();
 ///
 /// See https://ia.cr/2016/1188 for definition of Farfalle.
 /// Also see https://ia.cr/2018/767 for definition of Xoofff.
-#[derive(Clone, Copy)]
-pub struct Xoofff {
-    imask: [u32; LANE_CNT], // input mask
-    omask: [u32; LANE_CNT], // output mask
-    acc: [u32; LANE_CNT],   // accumulator
-    iblk: [u8; BLOCK_SIZE], // input message block ( buffer )
-    oblk: [u8; BLOCK_SIZE], // output message block ( buffer )
-    ioff: usize,            // offset into input message block
-    ooff: usize,            // offset into output message block
-    finalized: usize,       // is deck function state finalized ?
-}
+///
+/// It's `farfalle::Farfalle`, instantiated with Xoodoo\[6\], roll_Xc and roll_Xe, so see there
+/// for all routines, shared by every Farfalle instance. Routines specific to Xoofff ( e.g. state
+/// snapshots ) are implemented right here.
+///
+/// With `zeroize` feature, deck function state is wiped on drop and the type isn't `Copy`.
+pub type Xoofff = Farfalle<Xoodoo<ROUNDS>, RollXc, RollXe>;
+
+/// Xoofff with configurable # -of rounds of Xoodoo\[n_r\] permutation, for each phase of Farfalle
+/// i.e. B for mask derivation, C for compression, D for the middle ( i.e. accumulator to output
+/// mask ) and E for expansion, each of them must be <= 12, which is checked at compile-time.
+/// Handy for more conservative parameter sets or reduced round variants, for cryptanalysis.
+///
+/// All of them default to 6 s.t. `XoofffParams` ( i.e. `XoofffParams<6, 6, 6, 6>` ) is exactly
+/// same type as `Xoofff`.
+pub type XoofffParams<
+    const B: usize = ROUNDS,
+    const C: usize = ROUNDS,
+    const D: usize = ROUNDS,
+    const E: usize = ROUNDS,
+> = Farfalle<Xoodoo<B>, RollXc, RollXe, Xoodoo<C>, Xoodoo<D>, Xoodoo<E>>;
 
 impl Xoofff {
-    /// Create a new instance of Xoofff, with a key of byte length < 48, which
-    /// can be used for incrementally absorbing messages and squeezing output bytes.
-    #[inline(always)]
-    pub fn new(key: &[u8]) -> Self {
-        debug_assert!(
-            key.len() < BLOCK_SIZE,
-            "Key byte length must be < {}",
-            BLOCK_SIZE
-        );
-
-        // masked key derivation phase
-        let padded_key = pad10x(key);
-        let mut masked_key = bytes_to_le_words(&padded_key);
-        xoodoo::permute::<ROUNDS>(&mut masked_key);
-
-        Self {
-            imask: masked_key,
-            omask: [0u32; LANE_CNT],
-            acc: [0u32; LANE_CNT],
-            iblk: [0u8; BLOCK_SIZE],
-            oblk: [0u8; BLOCK_SIZE],
-            ioff: 0,
-            ooff: 0,
-            finalized: usize::MIN,
-        }
+    /// Exports complete deck function state ( i.e. masks, accumulator, buffered message/ output
+    /// block, offsets and phase ) as a versioned binary snapshot, so that it can be resumed later,
+    /// possibly in another process, using `import_state`. Resumed deck function behaves exactly
+    /// same as this one would have.
+    ///
+    /// Note, snapshot carries key-derived secret material, so treat it as secret as the key itself.
+    ///
+    /// Only offered by serial Xoofff, so it's not available on `crate::Xoofff` when `simd` feature
+    /// is enabled, which makes it the SIMD one.
+    #[cfg(feature = "alloc")]
+    pub fn export_state(&self) -> Vec<u8> {
+        self.encode_state(0).to_vec()
+    }
+
+    /// Exports deck function state, same as `export_state`, but appends a 32 -bytes tag, computed
+    /// using Xoofff keyed with `key` ( of byte length < 48 ), so that any tampering with snapshot is
+    /// detected when importing it using `import_state_authenticated`.
+    #[cfg(feature = "alloc")]
+    pub fn export_state_authenticated(&self, key: &[u8]) -> Vec<u8> {
+        let state = self.encode_state(snapshot::FLAG_AUTHENTICATED);
+
+        let mut res = Vec::with_capacity(STATE_LEN + TAG_LEN);
+        res.extend_from_slice(&state);
+        res.extend_from_slice(&state_tag(key, &state));
+        res
     }
 
-    /// Given a message M of byte length N (>=0), this routine can be used for absorbing
-    /// message bytes into the state of the deck function Xoofff, following algorithm 1,
-    /// defined in Farfalle specification https://ia.cr/2016/1188.
+    /// Imports deck function state from a snapshot, previously exported using `export_state`,
+    /// checking its length, format version and consistency of encoded phase and offsets.
     ///
-    /// Note, this function can be called multiple times until Xoofff state is finalized. Once
-    /// finalized, bytes can be squeezed out of deck function state. Even after finalization
-    /// new absorption->finalization->squeezing phase can be started by calling restart function.
-    #[inline(always)]
-    pub fn absorb(&mut self, msg: &[u8]) {
-        if self.finalized == usize::MAX {
-            return;
+    /// Note, masks and accumulator can't be checked against each other, without the key, so a
+    /// corrupted snapshot may still be imported. Use `import_state_authenticated`, when snapshot
+    /// comes from untrusted storage.
+    pub fn import_state(bytes: &[u8]) -> Result<Self, StateError> {
+        if bytes.len() != STATE_LEN {
+            return Err(StateError::InvalidLength);
         }
+        Self::decode_state(bytes.try_into().unwrap(), 0)
+    }
 
-        let blk_cnt = (self.ioff + msg.len()) / BLOCK_SIZE;
-        let mut moff = 0;
-
-        for _ in 0..blk_cnt {
-            let byte_cnt = BLOCK_SIZE - self.ioff;
+    /// Imports deck function state from an authenticated snapshot, previously exported using
+    /// `export_state_authenticated`, rejecting it if authentication tag, recomputed using `key`,
+    /// doesn't match the one carried by snapshot.
+    pub fn import_state_authenticated(bytes: &[u8], key: &[u8]) -> Result<Self, StateError> {
+        if bytes.len() != STATE_LEN + TAG_LEN {
+            return Err(StateError::InvalidLength);
+        }
 
-            self.iblk[self.ioff..].copy_from_slice(&msg[moff..(moff + byte_cnt)]);
-            let mut words = bytes_to_le_words(&self.iblk);
+        let (state, tag) = bytes.split_at(STATE_LEN);
+        let state: &[u8; STATE_LEN] = state.try_into().unwrap();
 
-            debug_assert_eq!(LANE_CNT, 12);
-            unroll! {
-                for i in 0..12 {
-                    words[i] ^= self.imask[i];
-                }
-            }
+        if !snapshot::ct_eq(&state_tag(key, state), tag) {
+            return Err(StateError::AuthenticationFailed);
+        }
+        Self::decode_state(state, snapshot::FLAG_AUTHENTICATED)
+    }
 
-            xoodoo::permute::<ROUNDS>(&mut words);
+    /// Serializes deck function state into snapshot format, with given header flags.
+    fn encode_state(&self, flags: u8) -> [u8; STATE_LEN] {
+        let mut res = [0u8; STATE_LEN];
+
+        res[..4].copy_from_slice(&snapshot::MAGIC);
+        res[4] = snapshot::VERSION;
+        res[5] = flags;
+        res[6] = (self.finalized == usize::MAX) as u8;
+        res[7] = self.ioff as u8;
+        res[8] = self.ooff as u8;
+
+        res[9..17].copy_from_slice(&self.oidx.to_le_bytes());
+        res[17..25].copy_from_slice(&self.oskip.to_le_bytes());
+
+        let mut off = snapshot::HEADER_LEN;
+        for words in [
+            &self.imask,
+            &self.ibase,
+            &self.omask,
+            &self.obase,
+            &self.acc,
+        ] {
+            words_to_le_bytes(words, (&mut res[off..off + BLOCK_SIZE]).try_into().unwrap());
+            off += BLOCK_SIZE;
+        }
+        for blk in [&self.iblk, &self.oblk] {
+            res[off..off + BLOCK_SIZE].copy_from_slice(blk);
+            off += BLOCK_SIZE;
+        }
 
-            debug_assert_eq!(LANE_CNT, 12);
-            unroll! {
-                for i in 0..12 {
-                    self.acc[i] ^= words[i];
-                }
-            }
+        res
+    }
 
-            rolling::roll_xc(&mut self.imask);
-            moff += byte_cnt;
-            self.ioff = 0;
+    /// Deserializes deck function state from snapshot format, expecting given header flags.
+    fn decode_state(bytes: &[u8; STATE_LEN], flags: u8) -> Result<Self, StateError> {
+        if bytes[..4] != snapshot::MAGIC {
+            return Err(StateError::InvalidMagic);
+        }
+        if bytes[4] != snapshot::VERSION {
+            return Err(StateError::UnsupportedVersion(bytes[4]));
+        }
+        if bytes[5] & !snapshot::FLAG_AUTHENTICATED != 0 {
+            return Err(StateError::Malformed);
+        }
+        if bytes[5] != flags {
+            return Err(StateError::UnexpectedAuthentication);
         }
 
-        let rm_bytes = msg.len() - moff;
-        let dst_frm = self.ioff;
-        let dst_to = dst_frm + rm_bytes;
+        let finalized = match bytes[6] {
+            0 => usize::MIN,
+            1 => usize::MAX,
+            _ => return Err(StateError::Malformed),
+        };
+        let ioff = bytes[7] as usize;
+        let ooff = bytes[8] as usize;
+        let oidx = u64::from_le_bytes(bytes[9..17].try_into().unwrap());
+        let oskip = u64::from_le_bytes(bytes[17..25].try_into().unwrap());
+
+        if ioff >= BLOCK_SIZE
+            || ooff >= BLOCK_SIZE
+            || (finalized == usize::MIN && (ooff != 0 || oidx != 0 || oskip != 0))
+            || (finalized == usize::MAX && (ioff != 0 || oidx < oskip / BLOCK_SIZE as u64))
+        {
+            return Err(StateError::Malformed);
+        }
 
-        self.iblk[dst_frm..dst_to].copy_from_slice(&msg[moff..]);
-        self.ioff += rm_bytes;
+        let block = |i: usize| -> &[u8; BLOCK_SIZE] {
+            let off = snapshot::HEADER_LEN + i * BLOCK_SIZE;
+            bytes[off..off + BLOCK_SIZE].try_into().unwrap()
+        };
+
+        Ok(Self {
+            imask: bytes_to_le_words(block(0)),
+            ibase: bytes_to_le_words(block(1)),
+            omask: bytes_to_le_words(block(2)),
+            obase: bytes_to_le_words(block(3)),
+            acc: bytes_to_le_words(block(4)),
+            iblk: *block(5),
+            oblk: *block(6),
+            ioff,
+            ooff,
+            oidx,
+            oskip,
+            finalized,
+            _marker: PhantomData,
+        })
     }
+}
 
-    /// Given that arbitrary many message bytes are already absorbed into deck function
-    /// state, this routine can be used for finalizing the state, so that arbitrary many
-    /// bytes can be squeezed out of deck function state.
+#[cfg(feature = "rayon")]
+impl Xoofff {
+    /// Same as `absorb`, but full message blocks are compressed on all threads of rayon's global
+    /// thread pool. Message is split into chunks of 4096 blocks ( i.e. 192 kB ), each chunk starts
+    /// from its own input mask, obtained by jumping input mask ahead, and accumulators of all
+    /// chunks are XORed together, so that result is exactly same as what `absorb` would produce.
     ///
-    /// - Once finalized, calling this routine again on same object does nothing.
-    /// - Attempting to absorb new message bytes on already finalized state, does nothing.
-    /// - After finalization, one might start squeezing arbitrary many output bytes.
-    /// - After finishing squeezing, when new message arrives, arbitrary many bytes
-    /// can be consumed into deck function state, by restarting `absorb->finalize->squeeze` cycle.
+    /// For short messages ( i.e. < 2 chunks ), this simply falls back to `absorb`.
     ///
-    /// This routine implements portion of algorithm 1 of https://ia.cr/2016/1188.
-    #[inline(always)]
-    pub fn finalize(&mut self, domain_seperator: u8, ds_bit_width: usize, offset: usize) {
-        debug_assert!(
-            offset <= BLOCK_SIZE,
-            "Byte offset, considered during squeezing, must be <= 48 -bytes"
-        );
-        debug_assert!(
-            ds_bit_width <= 7,
-            "Domain seperator bit width is not allowed to be > 7"
-        );
+    /// Only offered by serial Xoofff, so it's not available on `crate::Xoofff` when `simd` feature
+    /// is enabled, which makes it the SIMD one.
+    pub fn absorb_parallel(&mut self, msg: &[u8]) {
+        use rayon::prelude::*;
 
         if self.finalized == usize::MAX {
             return;
         }
 
-        let mask = (1u8 << ds_bit_width) - 1u8;
-        let pad_byte = (1u8 << ds_bit_width) | (domain_seperator & mask);
+        // first complete the partially filled message block, if any
+        let head = cmp::min((BLOCK_SIZE - self.ioff) % BLOCK_SIZE, msg.len());
+        self.absorb(&msg[..head]);
 
-        self.iblk[self.ioff..].fill(0);
-        self.iblk[self.ioff] = pad_byte;
+        let msg = &msg[head..];
+        let blk_cnt = msg.len() / BLOCK_SIZE;
 
-        let mut words = bytes_to_le_words(&self.iblk);
-
-        debug_assert_eq!(LANE_CNT, 12);
-        unroll! {
-            for i in 0..12 {
-                words[i] ^= self.imask[i];
-            }
+        if self.ioff != 0 || blk_cnt < 2 * PAR_CHUNK_BLOCKS {
+            self.absorb(msg);
+            return;
         }
 
-        xoodoo::permute::<ROUNDS>(&mut words);
+        let (blks, tail) = msg.split_at(blk_cnt * BLOCK_SIZE);
+        let imask = self.imask;
+
+        let acc = blks
+            .par_chunks(PAR_CHUNK_BLOCKS * BLOCK_SIZE)
+            .enumerate()
+            .map(|(i, chunk)| {
+                let mut mask = imask;
+                rolling::roll_xc_jump(&mut mask, (i * PAR_CHUNK_BLOCKS) as u64);
+
+                let mut acc = [0u32; LANE_CNT];
+                compress_blocks(&mut mask, &mut acc, chunk);
+
+                wipe(&mut mask);
+                acc
+            })
+            .reduce(
+                || [0u32; LANE_CNT],
+                |mut a, b| {
+                    a.iter_mut().zip(b.iter()).for_each(|(x, y)| *x ^= *y);
+                    a
+                },
+            );
+
+        self.acc
+            .iter_mut()
+            .zip(acc.iter())
+            .for_each(|(x, y)| *x ^= *y);
+        rolling::roll_xc_jump(&mut self.imask, blk_cnt as u64);
+
+        self.absorb(tail);
+    }
 
-        debug_assert_eq!(LANE_CNT, 12);
-        unroll! {
-            for i in 0..12 {
-                self.acc[i] ^= words[i];
-            }
-        }
+    /// Same as `squeeze`, but output blocks are computed on all threads of rayon's global thread
+    /// pool. Output masks are computed serially, by cheaply rolling output mask, only noting down
+    /// the one each chunk of 4096 blocks ( i.e. 192 kB ) starts with, and then permutations of all
+    /// chunks are fanned out across threads, so that output is exactly same as what `squeeze`
+    /// would produce, including when squeezing starts in the middle of an output block.
+    ///
+    /// For short outputs ( i.e. < 2 chunks ), this simply falls back to `squeeze`.
+    ///
+    /// Only offered by serial Xoofff, so it's not available on `crate::Xoofff` when `simd` feature
+    /// is enabled, which makes it the SIMD one.
+    pub fn squeeze_parallel(&mut self, out: &mut [u8]) {
+        use rayon::prelude::*;
 
-        rolling::roll_xc(&mut self.imask);
-        rolling::roll_xc(&mut self.imask);
+        if self.finalized != usize::MAX {
+            return;
+        }
 
-        self.iblk.fill(0);
-        self.ioff = 0;
-        self.finalized = usize::MAX;
+        // first drain the partially consumed output block, if any
+        let head = cmp::min((BLOCK_SIZE - self.ooff) % BLOCK_SIZE, out.len());
+        let (head, out) = out.split_at_mut(head);
+        self.squeeze(head);
 
-        self.omask.copy_from_slice(&self.acc);
-        xoodoo::permute::<ROUNDS>(&mut self.omask);
+        let blk_cnt = out.len() / BLOCK_SIZE;
+        if blk_cnt < 2 * PAR_CHUNK_BLOCKS {
+            self.squeeze(out);
+            return;
+        }
 
-        let mut words = self.omask;
-        xoodoo::permute::<ROUNDS>(&mut words);
+        // output block `oidx` is already buffered, all following ones are to be computed
+        let (blks, tail) = out.split_at_mut(blk_cnt * BLOCK_SIZE);
+        let (first, rest) = blks.split_at_mut(BLOCK_SIZE);
+        first.copy_from_slice(&self.oblk);
 
-        debug_assert_eq!(LANE_CNT, 12);
-        unroll! {
-            for i in 0..12 {
-                words[i] ^= self.imask[i];
+        let mut masks = Vec::with_capacity(rest.len().div_ceil(PAR_CHUNK_BLOCKS * BLOCK_SIZE));
+        for chunk in rest.chunks(PAR_CHUNK_BLOCKS * BLOCK_SIZE) {
+            masks.push(self.omask);
+            for _ in 0..(chunk.len() / BLOCK_SIZE) {
+                rolling::roll_xe(&mut self.omask);
             }
         }
 
-        words_to_le_bytes(&words, &mut self.oblk);
-        self.ooff = offset;
+        let imask = self.imask;
+        rest.par_chunks_mut(PAR_CHUNK_BLOCKS * BLOCK_SIZE)
+            .zip(masks.par_iter_mut())
+            .for_each(|(chunk, mask)| {
+                farfalle::expand_blocks::<Xoodoo<ROUNDS>, RollXe, false>(mask, &imask, chunk);
+                wipe(mask);
+            });
+
+        // `omask` is now the mask for output block `oidx + blk_cnt`, which gets buffered
+        self.expand();
+        self.oidx += blk_cnt as u64;
+        self.ooff = 0;
 
-        rolling::roll_xe(&mut self.omask);
+        self.squeeze(tail);
     }
+}
 
-    /// Given that N -many message bytes are already absorbed into deck function state and
-    /// state is finalized, this routine can be used for squeezing arbitrary many bytes out
-    /// of deck function state. One can call this function arbitrary many times, each time
-    /// requesting arbitrary many bytes, if and only if state is already finalized and it's
-    /// not yet restarted for processing another message using `absorb->finalize->squeeze` cycle.
-    ///
-    /// This routine implements last portion of algorithm 1 of https://ia.cr/2016/1188.
-    #[inline(always)]
-    pub fn squeeze(&mut self, out: &mut [u8]) {
-        if self.finalized != usize::MAX {
-            return;
-        }
+#[cfg(feature = "serde")]
+impl serde::Serialize for Xoofff {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.encode_state(0))
+    }
+}
 
-        let mut off = 0;
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Xoofff {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct StateVisitor;
 
-        while off < out.len() {
-            let read = cmp::min(BLOCK_SIZE - self.ooff, out.len() - off);
-            out[off..(off + read)].copy_from_slice(&self.oblk[self.ooff..(self.ooff + read)]);
+        impl<'de> serde::de::Visitor<'de> for StateVisitor {
+            type Value = Xoofff;
 
-            self.ooff += read;
-            off += read;
+            fn expecting(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                write!(f, "{} -bytes Xoofff state snapshot", STATE_LEN)
+            }
 
-            if self.ooff == BLOCK_SIZE {
-                let mut words = self.omask;
-                xoodoo::permute::<ROUNDS>(&mut words);
+            fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+                Xoofff::import_state(v).map_err(E::custom)
+            }
 
-                debug_assert_eq!(LANE_CNT, 12);
-                unroll! {
-                    for i in 0..12 {
-                        words[i] ^= self.imask[i];
-                    }
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> Result<Self::Value, A::Error> {
+                let mut bytes = [0u8; STATE_LEN];
+                for (i, b) in bytes.iter_mut().enumerate() {
+                    *b = seq
+                        .next_element()?
+                        .ok_or_else(|| serde::de::Error::invalid_length(i, &self))?;
                 }
-
-                words_to_le_bytes(&words, &mut self.oblk);
-                self.ooff = 0;
-
-                rolling::roll_xe(&mut self.omask);
+                if seq.next_element::<u8>()?.is_some() {
+                    return Err(serde::de::Error::invalid_length(STATE_LEN + 1, &self));
+                }
+                Xoofff::import_state(&bytes).map_err(serde::de::Error::custom)
             }
         }
+
+        deserializer.deserialize_bytes(StateVisitor)
     }
+}
 
-    /// Given that a message of arbitrary byte length is absorbed into deck function state and
-    /// it's also finalized i.e. ready to be squeezed, this function can be invoked when you've
-    /// new message waiting to be absorbed into deck function state and you need to restart the
-    /// `absorb->finalize->squeeze` cycle.
-    ///
-    /// Note, if the deck function state is not yet finalized, calling this function should do nothing.
-    /// Remember you're very much allowed to restart `absorb->finalize->squeeze` cycle any number of times
-    /// you want.
-    ///
-    /// This routine implements portion of algorithm 1 of https://ia.cr/2016/1188.
-    #[inline(always)]
-    pub fn restart(&mut self) {
-        if self.finalized != usize::MAX {
-            return;
+/// Compresses all full 48 -bytes blocks of message `msg` into accumulator `acc`, s.t. first
+/// block is masked using `mask`, which is rolled once after each block, see
+/// `farfalle::compress_blocks`.
+#[inline(always)]
+pub(crate) fn compress_blocks(mask: &mut [u32; LANE_CNT], acc: &mut [u32; LANE_CNT], msg: &[u8]) {
+    farfalle::compress_blocks::<Xoodoo<ROUNDS>, RollXc>(mask, acc, msg);
+}
+
+/// One-shot Xoofff, which can be evaluated at compile-time, say for deriving fixed subkeys or
+/// constant domain tags. Absorbs `msg` into deck function, keyed with `key` ( of byte length
+/// < 48 ), finalizes it with empty domain seperator and no offset, and squeezes N -bytes, so that
+/// output is same as
+///
+/// ```rust
+/// use xoofff::{xoofff_const, Xoofff};
+///
+/// const TAG: [u8; 32] = xoofff_const::<32>(b"key", b"domain");
+///
+/// let mut deck = Xoofff::new(b"key");
+/// deck.absorb(b"domain");
+/// deck.finalize(0, 0, 0);
+///
+/// let mut tag = [0u8; 32];
+/// deck.squeeze(&mut tag);
+///
+/// assert_eq!(tag, TAG);
+/// ```
+///
+/// Note, it uses only scalar Xoodoo permutation, so prefer `Xoofff` at runtime.
+pub const fn xoofff_const<const N: usize>(key: &[u8], msg: &[u8]) -> [u8; N] {
+    assert!(key.len() < BLOCK_SIZE, "Key byte length must be < 48");
+
+    // masked key derivation phase
+    let mut imask = bytes_to_le_words(&pad10x(key));
+    xoodoo::permute::<ROUNDS>(&mut imask);
+
+    // compression phase, last block being padded, possibly empty, message block
+    let mut acc = [0u32; LANE_CNT];
+    let mut msg = msg;
+    while let Some((blk, rest)) = msg.split_first_chunk::<BLOCK_SIZE>() {
+        compress_block_const(&mut imask, &mut acc, blk);
+        msg = rest;
+    }
+    compress_block_const(&mut imask, &mut acc, &pad10x(msg));
+    rolling::roll_xc(&mut imask);
+
+    // expansion phase
+    let mut omask = acc;
+    xoodoo::permute::<ROUNDS>(&mut omask);
+
+    let mut out = [0u8; N];
+    let mut blk = [0u8; BLOCK_SIZE];
+    let mut off = 0;
+    while off < N {
+        let mut words = omask;
+        xoodoo::permute::<ROUNDS>(&mut words);
+
+        let mut i = 0;
+        while i < LANE_CNT {
+            words[i] ^= imask[i];
+            i += 1;
         }
+        words_to_le_bytes(&words, &mut blk);
 
-        self.omask.fill(0);
-        self.oblk.fill(0);
-        self.ooff = 0;
-        self.finalized = usize::MIN;
+        let mut i = 0;
+        while i < BLOCK_SIZE && off + i < N {
+            out[off + i] = blk[i];
+            i += 1;
+        }
+
+        rolling::roll_xe(&mut omask);
+        off += BLOCK_SIZE;
+    }
+
+    out
+}
+
+/// Compresses a single message block into accumulator, rolling input mask forward, same as
+/// `compress_blocks`, but usable in const contexts.
+const fn compress_block_const(
+    mask: &mut [u32; LANE_CNT],
+    acc: &mut [u32; LANE_CNT],
+    blk: &[u8; BLOCK_SIZE],
+) {
+    let mut words = bytes_to_le_words(blk);
+
+    let mut i = 0;
+    while i < LANE_CNT {
+        words[i] ^= mask[i];
+        i += 1;
+    }
+
+    xoodoo::permute::<ROUNDS>(&mut words);
+
+    let mut i = 0;
+    while i < LANE_CNT {
+        acc[i] ^= words[i];
+        i += 1;
     }
+
+    rolling::roll_xc(mask);
+}
+
+/// Computes authentication tag over exported deck function state, using Xoofff keyed with `key`.
+fn state_tag(key: &[u8], state: &[u8; STATE_LEN]) -> [u8; TAG_LEN] {
+    let mut tag = [0u8; TAG_LEN];
+
+    let mut deck = Xoofff::new(key);
+    deck.absorb(state);
+    deck.finalize(
+        snapshot::TAG_DOMAIN_SEPERATOR,
+        snapshot::TAG_DS_BIT_WIDTH,
+        0,
+    );
+    deck.squeeze(&mut tag);
+
+    tag
+}
+
+/// Overwrites given buffer with zeros. When `zeroize` feature is enabled, it's done in a
+/// way that compiler can't optimize away, even if the buffer is never read again.
+#[cfg(feature = "zeroize")]
+#[inline(always)]
+pub(crate) fn wipe<T: zeroize::DefaultIsZeroes>(buf: &mut [T]) {
+    buf.zeroize();
+}
+
+/// Overwrites given buffer with zeros.
+#[cfg(not(feature = "zeroize"))]
+#[inline(always)]
+pub(crate) fn wipe<T: Copy + Default>(buf: &mut [T]) {
+    buf.fill(T::default());
 }
 
 /// Given a message of length N -bytes ( s.t. N < 48 ), this routine pads the
 /// message following pad10* rule such that padded message length becomes 48 -bytes.
 #[inline(always)]
-pub(crate) fn pad10x(msg: &[u8]) -> [u8; BLOCK_SIZE] {
+pub(crate) const fn pad10x(msg: &[u8]) -> [u8; BLOCK_SIZE] {
     debug_assert!(
         msg.len() < BLOCK_SIZE,
-        "Paddable message length must be < {}",
-        BLOCK_SIZE
+        "Paddable message length must be < 48"
     );
 
     let mlen = msg.len();
     let mut res = [0u8; BLOCK_SIZE];
 
-    res[..mlen].copy_from_slice(msg);
+    res.split_at_mut(mlen).0.copy_from_slice(msg);
     res[mlen] = 0x01;
 
     res
@@ -280,24 +529,51 @@ pub(crate) fn pad10x(msg: &[u8]) -> [u8; BLOCK_SIZE] {
 /// 32 -bit integers (= u32) s.t. four consecutive bytes are placed in little endian order
 /// in a u32 word.
 #[inline(always)]
-pub(crate) fn bytes_to_le_words(bytes: &[u8; BLOCK_SIZE]) -> [u32; LANE_CNT] {
+pub(crate) const fn bytes_to_le_words(bytes: &[u8; BLOCK_SIZE]) -> [u32; LANE_CNT] {
     let mut words = [0u32; LANE_CNT];
 
-    debug_assert_eq!(LANE_CNT, 12);
     unroll! {
         for i in 0..12 {
-            words[i] = u32::from_le_bytes(bytes[i * 4..(i + 1) * 4].try_into().unwrap());
+            words[i] = u32::from_le_bytes([
+                bytes[i * 4],
+                bytes[i * 4 + 1],
+                bytes[i * 4 + 2],
+                bytes[i * 4 + 3],
+            ]);
         }
     }
     words
 }
 
 #[inline(always)]
-pub(crate) fn words_to_le_bytes(words: &[u32; LANE_CNT], bytes: &mut [u8; BLOCK_SIZE]) {
+pub(crate) const fn words_to_le_bytes(words: &[u32; LANE_CNT], bytes: &mut [u8; BLOCK_SIZE]) {
+    unroll! {
+        for i in 0..12 {
+            let word = words[i].to_le_bytes();
+
+            bytes[i * 4] = word[0];
+            bytes[i * 4 + 1] = word[1];
+            bytes[i * 4 + 2] = word[2];
+            bytes[i * 4 + 3] = word[3];
+        }
+    }
+}
+
+/// XORs bytes of `src` into `dst` s.t. both are of same byte length.
+#[inline(always)]
+pub(crate) fn xor_bytes(dst: &mut [u8], src: &[u8]) {
+    debug_assert_eq!(dst.len(), src.len());
+    dst.iter_mut().zip(src.iter()).for_each(|(d, s)| *d ^= *s);
+}
+
+/// XORs 12 unsigned 32 -bit words into 48 -bytes, interpreting those bytes as little-endian words.
+#[inline(always)]
+pub(crate) fn xor_le_words(words: &[u32; LANE_CNT], bytes: &mut [u8; BLOCK_SIZE]) {
     debug_assert_eq!(LANE_CNT, 12);
     unroll! {
         for i in 0..12 {
-            bytes[i * 4..(i + 1) * 4].copy_from_slice(&words[i].to_le_bytes());
+            let word = u32::from_le_bytes(bytes[i * 4..(i + 1) * 4].try_into().unwrap());
+            bytes[i * 4..(i + 1) * 4].copy_from_slice(&(word ^ words[i]).to_le_bytes());
         }
     }
 }